@@ -0,0 +1,57 @@
+//! Times the line-independent solutions on large generated inputs. Compare
+//! `cargo bench --bench parallel` with
+//! `cargo bench --bench parallel --features parallel`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use aoc_12023::{
+    day_01, day_02, day_04, day_05,
+    generate::{self, Rng},
+};
+
+const LINES: usize = 200_000;
+const SEEDS: usize = 20_000;
+const RUNS: u32 = 5;
+
+fn time<T>(name: &str, f: impl Fn() -> T) {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        black_box(f());
+        best = best.min(start.elapsed());
+    }
+    println!("{name:<24} {best:>12.2?}");
+}
+
+fn main() {
+    let rng = &mut Rng::new(12023);
+    let calibration = generate::calibration_document(rng, LINES);
+    let games = generate::game_records(rng, LINES, 6);
+    let cards = generate::scratchcards(rng, LINES, 10, 25);
+    let almanac = generate::almanac(rng, SEEDS, 30);
+
+    println!(
+        "parallel feature {}",
+        if cfg!(feature = "parallel") {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    time("day 1 part 1", || {
+        day_01::retrieve_calibration_value_part1(&calibration)
+    });
+    time("day 1 part 2", || {
+        day_01::retrieve_calibration_value_part2(&calibration)
+    });
+    time("day 2 part 1", || {
+        day_02::get_ids_of_possible_games(&games, day_02::RGB_DICE_COUNTS)
+    });
+    time("day 4 part 1", || day_04::get_points_from_cards(&cards));
+    time("day 5 part 1", || {
+        day_05::get_lowest_location_per_seed(&almanac)
+    });
+}
@@ -0,0 +1,6 @@
+#![no_main]
+
+use aoc_12023::fuzz;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| fuzz::run(fuzz::day_03_schematic, data));
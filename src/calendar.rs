@@ -0,0 +1,274 @@
+//! Dates in the Holocene calendar, which counts years from 10000 BC: the
+//! Gregorian year 2023 is 12023 HE.
+//!
+//! Years are given in astronomical numbering, where 1 BC is the year 0, so
+//! converting between the two calendars is a matter of adding or
+//! subtracting [`HOLOCENE_OFFSET`].
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+pub const HOLOCENE_OFFSET: i64 = 10_000;
+
+/// The year of this edition of the event, in the Holocene calendar.
+pub const EVENT_YEAR: i64 = 12023;
+
+/// The last day of the event with a puzzle.
+pub const LAST_DAY: u32 = 25;
+
+/// Puzzles unlock at midnight UTC-5.
+const UNLOCK_HOUR_UTC: u64 = 5;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub const fn to_holocene(gregorian_year: i64) -> i64 {
+    gregorian_year + HOLOCENE_OFFSET
+}
+
+pub const fn to_gregorian(holocene_year: i64) -> i64 {
+    holocene_year - HOLOCENE_OFFSET
+}
+
+/// The event year as it appears in output, e.g. "12023 HE".
+pub fn event_label() -> String {
+    format!("{EVENT_YEAR} HE")
+}
+
+/// A day in the proleptic Gregorian calendar, displayed with its Holocene
+/// year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    /// The Gregorian year.
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// Returns `None` unless `month` and `day` exist in the Gregorian `year`.
+    pub fn new(year: i64, month: u32, day: u32) -> Option<Self> {
+        ((1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day))
+            .then_some(Date { year, month, day })
+    }
+
+    pub fn from_holocene(year: i64, month: u32, day: u32) -> Option<Self> {
+        Date::new(to_gregorian(year), month, day)
+    }
+
+    /// The date `days` after 1 January 1970.
+    pub fn from_days(days: i64) -> Self {
+        // Counts from 1 March 0000, so that leap days end each 400-year era.
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = era * 400 + year_of_era + i64::from(month <= 2);
+
+        Date {
+            year,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    /// The number of days since 1 January 1970, negative before it.
+    pub fn days(&self) -> i64 {
+        let year = self.year - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let shifted_month = i64::from((self.month + 9) % 12);
+        let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    pub fn gregorian_year(&self) -> i64 {
+        self.year
+    }
+
+    pub fn holocene_year(&self) -> i64 {
+        to_holocene(self.year)
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{:02}-{:02} HE",
+            self.holocene_year(),
+            self.month,
+            self.day
+        )
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The current time in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// The date at `now`, in seconds since the Unix epoch, in UTC-5: the time
+/// zone in which puzzles unlock at midnight.
+pub fn puzzle_date(now: u64) -> Date {
+    let seconds = now as i64 - (UNLOCK_HOUR_UTC * 60 * 60) as i64;
+    Date::from_days(seconds.div_euclid(SECONDS_PER_DAY as i64))
+}
+
+/// When the puzzle of `day` unlocks, in seconds since the Unix epoch, or
+/// `None` if the event has no such day.
+pub fn unlock_time(day: u32) -> Option<u64> {
+    if !(1..=LAST_DAY).contains(&day) {
+        return None;
+    }
+    let date = Date::from_holocene(EVENT_YEAR, 12, day)?;
+
+    Some(date.days() as u64 * SECONDS_PER_DAY + UNLOCK_HOUR_UTC * 60 * 60)
+}
+
+pub fn is_unlocked(day: u32, now: u64) -> bool {
+    unlock_time(day).is_some_and(|unlock| unlock <= now)
+}
+
+/// The next day to unlock and how long until it does, or `None` once every
+/// day is unlocked.
+pub fn next_unlock(now: u64) -> Option<(u32, Duration)> {
+    (1..=LAST_DAY).find_map(|day| {
+        let unlock = unlock_time(day)?;
+        (unlock > now).then(|| (day, Duration::from_secs(unlock - now)))
+    })
+}
+
+/// `HH:MM:SS`, prefixed with the days if there are any.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    match seconds / SECONDS_PER_DAY {
+        0 => time,
+        days => format!("{days}d {time}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holocene_years() {
+        assert_eq!(12023, to_holocene(2023));
+        assert_eq!(2023, to_gregorian(EVENT_YEAR));
+        // 1 BC and 10000 BC in astronomical numbering.
+        assert_eq!(10000, to_holocene(0));
+        assert_eq!(1, to_holocene(-9999));
+        assert_eq!("12023 HE", event_label());
+    }
+
+    #[test]
+    fn civil_dates() {
+        let date = |year, month, day| Date::new(year, month, day).unwrap();
+        assert_eq!(0, date(1970, 1, 1).days());
+        assert_eq!(19_692, date(2023, 12, 1).days());
+        assert_eq!(-719_468, date(0, 3, 1).days());
+        assert_eq!(date(2023, 12, 1), Date::from_days(19_692));
+        assert_eq!(Some(date(2023, 12, 1)), Date::from_holocene(12023, 12, 1));
+        assert_eq!("12023-12-01 HE", date(2023, 12, 1).to_string());
+        assert_eq!("9999-01-01 HE", date(-1, 1, 1).to_string());
+
+        assert!(Date::new(2024, 2, 29).is_some());
+        assert!(Date::new(2023, 2, 29).is_none());
+        assert!(Date::new(2000, 2, 29).is_some());
+        assert!(Date::new(1900, 2, 29).is_none());
+        assert!(Date::new(2023, 4, 31).is_none());
+        assert!(Date::new(2023, 13, 1).is_none());
+        assert!(Date::new(2023, 12, 0).is_none());
+
+        // Every day of a span covering leap centuries and years before 0.
+        let mut expected = date(-401, 1, 1);
+        for days in expected.days()..=date(2401, 12, 31).days() {
+            let date = Date::from_days(days);
+            assert_eq!(days, date.days());
+            assert_eq!(Some(date), Date::new(date.year, date.month, date.day));
+            assert_eq!(expected, date);
+            expected = Date::new(date.year, date.month, date.day + 1)
+                .or_else(|| Date::new(date.year, date.month + 1, 1))
+                .unwrap_or_else(|| Date::new(date.year + 1, 1, 1).unwrap());
+        }
+    }
+
+    #[test]
+    fn unlocks() {
+        assert_eq!(Some(1_701_406_800), unlock_time(1));
+        assert_eq!(Some(1_701_406_800 + 24 * SECONDS_PER_DAY), unlock_time(25));
+        assert_eq!(None, unlock_time(0));
+        assert_eq!(None, unlock_time(26));
+
+        assert!(!is_unlocked(1, 1_701_406_799));
+        assert!(is_unlocked(1, 1_701_406_800));
+        assert!(!is_unlocked(2, 1_701_406_800));
+        assert!(!is_unlocked(26, u64::MAX));
+
+        assert_eq!(
+            Some((1, Duration::from_secs(3 * SECONDS_PER_DAY + 1))),
+            next_unlock(1_701_406_800 - 3 * SECONDS_PER_DAY - 1)
+        );
+        assert_eq!(
+            Some((2, Duration::from_secs(SECONDS_PER_DAY))),
+            next_unlock(1_701_406_800)
+        );
+        assert_eq!(None, next_unlock(unlock_time(25).unwrap()));
+
+        assert_eq!(Date::new(2023, 11, 30), Some(puzzle_date(1_701_406_799)));
+        assert_eq!(Date::new(2023, 12, 1), Some(puzzle_date(1_701_406_800)));
+        assert_eq!(Date::new(1969, 12, 31), Some(puzzle_date(0)));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!("00:00:00", format_duration(Duration::ZERO));
+        assert_eq!("01:02:03", format_duration(Duration::from_secs(3723)));
+        assert_eq!(
+            "2d 00:00:59",
+            format_duration(Duration::from_millis(2 * 86_400_000 + 59_999))
+        );
+    }
+}
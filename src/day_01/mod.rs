@@ -1,56 +1,79 @@
+use std::io::BufRead;
+
+use itertools::Itertools;
+
+use crate::{
+    parallel,
+    parse::{self, Line, ParseError, ReadError},
+};
+
 const STRINGIFIED_NUMBERS: [&str; 9] = [
     "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
 ];
 
 pub fn retrieve_calibration_value_part1(input: &str) -> u32 {
-    input
-        .lines()
-        .map(parse_line_part1)
+    let lines = parse::lines(input).collect_vec();
+    parallel::map(&lines, |&line| parse_line_part1(line).unwrap())
+        .into_iter()
         .map(|(ten, one)| ten * 10 + one)
         .sum()
 }
 
-fn parse_line_part1(line: &str) -> (u32, u32) {
-    let ten = line
-        .chars()
-        .find(|c| c.is_ascii_digit())
-        .map(|c| c.to_digit(10))
-        .unwrap()
-        .unwrap();
-    let one = line
-        .chars()
-        .rev()
-        .find(|c| c.is_ascii_digit())
-        .map(|c| c.to_digit(10))
-        .unwrap()
-        .unwrap();
-
-    (ten, one)
+/// Like [`retrieve_calibration_value_part1`], but reads the document line by
+/// line.
+pub fn retrieve_calibration_value_part1_from_reader(
+    reader: impl BufRead,
+) -> Result<u64, ReadError> {
+    parse::try_fold_lines(reader, 0u64, |sum, line| {
+        let (ten, one) = parse_line_part1(line)?;
+        Ok(sum + u64::from(ten * 10 + one))
+    })
+}
+
+pub(crate) fn parse_line_part1(line: Line) -> Result<(u32, u32), ParseError> {
+    let mut digits = line.text().chars().filter_map(|c| c.to_digit(10));
+    let ten = digits
+        .next()
+        .ok_or_else(|| line.error(0, "expected a digit"))?;
+    let one = digits.next_back().unwrap_or(ten);
+
+    Ok((ten, one))
 }
 
 pub fn retrieve_calibration_value_part2(input: &str) -> u32 {
-    input
-        .lines()
-        .map(parse_line_part2)
+    let lines = parse::lines(input).collect_vec();
+    parallel::map(&lines, |&line| parse_line_part2(line).unwrap())
+        .into_iter()
         .map(|(ten, one)| ten * 10 + one)
         .sum()
 }
 
-fn parse_line_part2(line: &str) -> (u32, u32) {
+/// Like [`retrieve_calibration_value_part2`], but reads the document line by
+/// line.
+pub fn retrieve_calibration_value_part2_from_reader(
+    reader: impl BufRead,
+) -> Result<u64, ReadError> {
+    parse::try_fold_lines(reader, 0u64, |sum, line| {
+        let (ten, one) = parse_line_part2(line)?;
+        Ok(sum + u64::from(ten * 10 + one))
+    })
+}
+
+pub(crate) fn parse_line_part2(line: Line) -> Result<(u32, u32), ParseError> {
     let mut strings_to_be_matched = vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"];
     strings_to_be_matched.extend(STRINGIFIED_NUMBERS);
 
     let mut stringified_matches = strings_to_be_matched
         .into_iter()
-        .flat_map(|stringified_number| line.match_indices(stringified_number))
+        .flat_map(|stringified_number| line.text().match_indices(stringified_number))
         .collect::<Vec<(usize, &str)>>();
 
-    stringified_matches.sort_by(|a, b| a.0.cmp(&b.0));
+    stringified_matches.sort_by_key(|a| a.0);
 
-    (
-        map_to_u32(stringified_matches[0].1),
-        map_to_u32(stringified_matches.last().unwrap().1),
-    )
+    match (stringified_matches.first(), stringified_matches.last()) {
+        (Some(first), Some(last)) => Ok((map_to_u32(first.1), map_to_u32(last.1))),
+        _ => Err(line.error(0, "expected a digit or a spelled out number")),
+    }
 }
 
 fn map_to_u32(input: &str) -> u32 {
@@ -66,6 +89,23 @@ fn map_to_u32(input: &str) -> u32 {
     }
 }
 
+/// The example of part 1 from the puzzle description.
+pub(crate) const EXAMPLE_PART_1: &str = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet
+";
+
+/// The example of part 2 from the puzzle description, with spelled out digits.
+pub(crate) const EXAMPLE_PART_2: &str = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen
+";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,6 +128,34 @@ treb7uchet";
         );
     }
 
+    #[test]
+    fn from_reader() {
+        assert_eq!(
+            61,
+            retrieve_calibration_value_part1_from_reader(
+                "1abc2\r\npqr3stu8vwx\r\nab1\r\n".as_bytes()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            281,
+            retrieve_calibration_value_part2_from_reader(EXAMPLE_PART_2.as_bytes()).unwrap()
+        );
+    }
+
+    #[test]
+    fn lines_without_digits() {
+        assert_eq!(
+            "line 2, column 1: expected a digit",
+            parse::lines("1abc2\nabc")
+                .map(parse_line_part1)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(parse_line_part2(Line::new(1, "zero")).is_err());
+    }
+
     #[test]
     fn part_2_example() {
         let input = "two1nine
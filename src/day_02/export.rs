@@ -0,0 +1,27 @@
+use super::Game;
+
+/// Serializes the games as a JSON array of `{"id", "samples"}` objects.
+pub fn games_to_json(games: &[Game]) -> String {
+    serde_json::to_string(games).expect("games serialize to JSON")
+}
+
+/// Serializes the games as CSV with one row per sample, numbered from 1
+/// within each game.
+pub fn games_to_csv(games: &[Game]) -> String {
+    let mut csv = "game,sample,red,green,blue\n".to_owned();
+
+    for game in games {
+        for (index, sample) in game.samples.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                game.id,
+                index + 1,
+                sample.red,
+                sample.green,
+                sample.blue
+            ));
+        }
+    }
+
+    csv
+}
@@ -0,0 +1,389 @@
+use std::{error::Error, fmt};
+
+use super::{DiceCombination, Game};
+
+/// A parsed filter expression such as `red > 10 and blue < 3`.
+///
+/// Colour names compare against the highest count of that colour drawn in
+/// the game (its minimal set), unless they appear inside an
+/// `any sample has ...`/`all samples have ...` clause, where they compare
+/// against the individual sample.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    Compare(Metric, Comparison, u32),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Any(Box<Filter>),
+    All(Box<Filter>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Id,
+    Red,
+    Green,
+    Blue,
+    Power,
+    Samples,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for FilterError {}
+
+impl Filter {
+    pub fn parse(expression: &str) -> Result<Self, FilterError> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser {
+            tokens,
+            index: 0,
+            end: expression.len(),
+            depth: 0,
+        };
+        let filter = parser.parse_or()?;
+
+        match parser.peek() {
+            Some(token) => Err(FilterError {
+                position: token.position,
+                message: format!("unexpected '{}'", token.kind),
+            }),
+            None => Ok(filter),
+        }
+    }
+
+    pub fn matches(&self, game: &Game) -> bool {
+        self.evaluate(game, None)
+    }
+
+    fn evaluate(&self, game: &Game, sample: Option<&DiceCombination>) -> bool {
+        match self {
+            Filter::Compare(metric, comparison, value) => {
+                comparison.apply(metric.value_of(game, sample), u128::from(*value))
+            }
+            Filter::And(left, right) => left.evaluate(game, sample) && right.evaluate(game, sample),
+            Filter::Or(left, right) => left.evaluate(game, sample) || right.evaluate(game, sample),
+            Filter::Not(inner) => !inner.evaluate(game, sample),
+            Filter::Any(inner) => game
+                .samples
+                .iter()
+                .any(|sample| inner.evaluate(game, Some(sample))),
+            Filter::All(inner) => game
+                .samples
+                .iter()
+                .all(|sample| inner.evaluate(game, Some(sample))),
+        }
+    }
+}
+
+impl Metric {
+    fn value_of(self, game: &Game, sample: Option<&DiceCombination>) -> u128 {
+        let dice = match sample {
+            Some(sample) => sample.clone(),
+            None => game.get_minimal_set(),
+        };
+
+        match self {
+            Metric::Id => u128::from(game.id),
+            Metric::Red => u128::from(dice.red),
+            Metric::Green => u128::from(dice.green),
+            Metric::Blue => u128::from(dice.blue),
+            Metric::Power => dice.power(),
+            Metric::Samples => game.samples.len() as u128,
+        }
+    }
+}
+
+impl Comparison {
+    fn apply(self, left: u128, right: u128) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "!=",
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Word(String),
+    Number(u32),
+    Comparison(Comparison),
+    OpenParen,
+    CloseParen,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Word(word) => write!(f, "{word}"),
+            TokenKind::Number(number) => write!(f, "{number}"),
+            TokenKind::Comparison(comparison) => write!(f, "{comparison}"),
+            TokenKind::OpenParen => write!(f, "("),
+            TokenKind::CloseParen => write!(f, ")"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = vec![];
+    let mut chars = expression.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        let kind = if c.is_whitespace() {
+            chars.next();
+            continue;
+        } else if c.is_ascii_alphabetic() {
+            let mut word = String::new();
+            while let Some(&(_, c)) = chars.peek().filter(|(_, c)| c.is_ascii_alphabetic()) {
+                word.push(c.to_ascii_lowercase());
+                chars.next();
+            }
+            TokenKind::Word(word)
+        } else if c.is_ascii_digit() {
+            let mut number = String::new();
+            while let Some(&(_, c)) = chars.peek().filter(|(_, c)| c.is_ascii_digit()) {
+                number.push(c);
+                chars.next();
+            }
+            TokenKind::Number(number.parse().map_err(|_| FilterError {
+                position,
+                message: format!("number '{number}' is too large"),
+            })?)
+        } else {
+            chars.next();
+            let followed_by_equals = chars.next_if(|&(_, c)| c == '=').is_some();
+            match (c, followed_by_equals) {
+                ('(', false) => TokenKind::OpenParen,
+                (')', false) => TokenKind::CloseParen,
+                ('<', false) => TokenKind::Comparison(Comparison::Less),
+                ('<', true) => TokenKind::Comparison(Comparison::LessOrEqual),
+                ('>', false) => TokenKind::Comparison(Comparison::Greater),
+                ('>', true) => TokenKind::Comparison(Comparison::GreaterOrEqual),
+                ('=', _) => TokenKind::Comparison(Comparison::Equal),
+                ('!', true) => TokenKind::Comparison(Comparison::NotEqual),
+                _ => {
+                    return Err(FilterError {
+                        position,
+                        message: format!("unexpected character '{c}'"),
+                    })
+                }
+            }
+        };
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+/// How deeply an expression may nest operators and parentheses, which keeps
+/// parsing, evaluating and dropping a filter from overflowing the stack.
+const MAX_DEPTH: usize = 200;
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Result<&Token, FilterError> {
+        let end = self.end;
+        let token = self.tokens.get(self.index).ok_or(FilterError {
+            position: end,
+            message: "unexpected end of expression".to_owned(),
+        })?;
+        self.index += 1;
+        Ok(token)
+    }
+
+    fn next_is_word(&mut self, word: &str) -> bool {
+        let is_word =
+            matches!(self.peek(), Some(Token { kind: TokenKind::Word(w), .. }) if w == word);
+        if is_word {
+            self.index += 1;
+        }
+        is_word
+    }
+
+    /// Enters one more level of nesting for the operator or parenthesis just
+    /// read.
+    fn descend(&mut self) -> Result<(), FilterError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(FilterError {
+                position: self.tokens[self.index - 1].position,
+                message: "expression is nested too deeply".to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    fn expect_word(&mut self, words: &[&str]) -> Result<(), FilterError> {
+        let token = self.next()?;
+        match &token.kind {
+            TokenKind::Word(word) if words.contains(&word.as_str()) => Ok(()),
+            kind => Err(FilterError {
+                position: token.position,
+                message: format!("expected '{}' but found '{kind}'", words.join("' or '")),
+            }),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, FilterError> {
+        let depth = self.depth;
+        let mut filter = self.parse_and()?;
+        while self.next_is_word("or") {
+            self.descend()?;
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        self.depth = depth;
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, FilterError> {
+        let depth = self.depth;
+        let mut filter = self.parse_unary()?;
+        while self.next_is_word("and") {
+            self.descend()?;
+            filter = Filter::And(Box::new(filter), Box::new(self.parse_unary()?));
+        }
+        self.depth = depth;
+        Ok(filter)
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, FilterError> {
+        if self.next_is_word("not") {
+            return Ok(Filter::Not(Box::new(self.parse_nested_unary()?)));
+        }
+        if self.next_is_word("any") {
+            self.expect_word(&["sample"])?;
+            self.expect_word(&["has"])?;
+            return Ok(Filter::Any(Box::new(self.parse_nested_unary()?)));
+        }
+        if self.next_is_word("all") {
+            self.expect_word(&["samples"])?;
+            self.expect_word(&["have"])?;
+            return Ok(Filter::All(Box::new(self.parse_nested_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_nested_unary(&mut self) -> Result<Filter, FilterError> {
+        self.descend()?;
+        let filter = self.parse_unary()?;
+        self.depth -= 1;
+        Ok(filter)
+    }
+
+    fn parse_primary(&mut self) -> Result<Filter, FilterError> {
+        let token = self.next()?;
+        let position = token.position;
+        let metric = match &token.kind {
+            TokenKind::OpenParen => {
+                self.descend()?;
+                let filter = self.parse_or()?;
+                self.depth -= 1;
+                let token = self.next()?;
+                return match token.kind {
+                    TokenKind::CloseParen => Ok(filter),
+                    _ => Err(FilterError {
+                        position: token.position,
+                        message: format!("expected ')' but found '{}'", token.kind),
+                    }),
+                };
+            }
+            TokenKind::Word(word) => match word.as_str() {
+                "id" => Metric::Id,
+                "red" => Metric::Red,
+                "green" => Metric::Green,
+                "blue" => Metric::Blue,
+                "power" => Metric::Power,
+                "samples" => Metric::Samples,
+                _ => {
+                    return Err(FilterError {
+                        position,
+                        message: format!("unknown metric '{word}'"),
+                    })
+                }
+            },
+            kind => {
+                return Err(FilterError {
+                    position,
+                    message: format!("expected a metric but found '{kind}'"),
+                })
+            }
+        };
+        if metric == Metric::Samples {
+            self.next_is_word("count");
+        }
+
+        let token = self.next()?;
+        let comparison = match token.kind {
+            TokenKind::Comparison(comparison) => comparison,
+            ref kind => {
+                return Err(FilterError {
+                    position: token.position,
+                    message: format!("expected a comparison but found '{kind}'"),
+                })
+            }
+        };
+
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::Number(value) => Ok(Filter::Compare(metric, comparison, value)),
+            ref kind => Err(FilterError {
+                position: token.position,
+                message: format!("expected a number but found '{kind}'"),
+            }),
+        }
+    }
+}
@@ -1,4 +1,18 @@
+use std::io::BufRead;
+
 use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    parallel,
+    parse::{self, Line, ParseError, ReadError, Span},
+};
+
+mod export;
+mod filter;
+
+pub use export::{games_to_csv, games_to_json};
+pub use filter::{Comparison, Filter, FilterError, Metric};
 
 pub const RGB_DICE_COUNTS: DiceCombination = DiceCombination {
     red: 12,
@@ -6,13 +20,22 @@ pub const RGB_DICE_COUNTS: DiceCombination = DiceCombination {
     blue: 14,
 };
 
-struct Game {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Game {
     id: u32,
     samples: Vec<DiceCombination>,
 }
 
 impl Game {
-    fn is_valid(&self, dice_in_bag: &DiceCombination) -> bool {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn samples(&self) -> &[DiceCombination] {
+        &self.samples
+    }
+
+    pub fn is_valid(&self, dice_in_bag: &DiceCombination) -> bool {
         self.samples.iter().all(|sample| {
             sample.red <= dice_in_bag.red
                 && sample.green <= dice_in_bag.green
@@ -20,10 +43,10 @@ impl Game {
         })
     }
 
-    fn get_minimal_set(self) -> DiceCombination {
+    pub fn get_minimal_set(&self) -> DiceCombination {
         let mut minimal_set = DiceCombination::new();
 
-        for sample in self.samples {
+        for sample in self.samples.iter() {
             minimal_set.red = minimal_set.red.max(sample.red);
             minimal_set.green = minimal_set.green.max(sample.green);
             minimal_set.blue = minimal_set.blue.max(sample.blue);
@@ -33,6 +56,7 @@ impl Game {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DiceCombination {
     red: u32,
     green: u32,
@@ -47,75 +71,139 @@ impl DiceCombination {
             blue: 0,
         }
     }
+
+    pub fn red(&self) -> u32 {
+        self.red
+    }
+
+    pub fn green(&self) -> u32 {
+        self.green
+    }
+
+    pub fn blue(&self) -> u32 {
+        self.blue
+    }
+
+    /// The product of the counts, which cannot overflow a `u128`.
+    pub fn power(&self) -> u128 {
+        u128::from(self.red) * u128::from(self.green) * u128::from(self.blue)
+    }
+}
+
+pub fn parse_games(input: &str) -> Result<Vec<Game>, ParseError> {
+    let lines = parse::lines(input).collect_vec();
+    parallel::map(&lines, |&line| parse_line(line))
+        .into_iter()
+        .collect()
 }
 
 pub fn get_ids_of_possible_games(input: &str, dice_in_bag: DiceCombination) -> Vec<u32> {
-    input
-        .lines()
-        .map(parse_line)
+    parse_games(input)
+        .unwrap()
+        .iter()
         .filter(|game| game.is_valid(&dice_in_bag))
-        .map(|game| game.id)
+        .map(Game::id)
         .collect_vec()
 }
 
-pub fn get_power_of_minimum_dice_sets_per_game(input: &str) -> Vec<u32> {
-    input
-        .lines()
-        .map(parse_line)
+pub fn get_power_of_minimum_dice_sets_per_game(input: &str) -> Vec<u128> {
+    parse_games(input)
+        .unwrap()
+        .iter()
         .map(Game::get_minimal_set)
-        .map(|minimal_set| minimal_set.red * minimal_set.green * minimal_set.blue)
+        .map(|minimal_set| minimal_set.power())
         .collect_vec()
 }
 
-fn parse_line(line: &str) -> Game {
-    let split_by_colon = line.split(':').collect_tuple::<(&str, &str)>().unwrap();
-    let game_id: &str = split_by_colon
-        .0
-        .split_ascii_whitespace()
-        .collect_tuple::<(&str, &str)>()
+pub fn get_ids_of_games_matching(input: &str, filter: &Filter) -> Vec<u32> {
+    parse_games(input)
         .unwrap()
-        .1;
-
-    Game {
-        id: game_id.parse().unwrap(),
-        samples: parse_samples(split_by_colon.1),
-    }
+        .iter()
+        .filter(|game| filter.matches(game))
+        .map(Game::id)
+        .collect_vec()
 }
 
-fn parse_samples(split_by_colon: &str) -> Vec<DiceCombination> {
-    split_by_colon
-        .split(';')
-        .map(|sample| sample.trim())
-        .map(parse_sample)
-        .collect_vec()
+/// Calls `f` with every game read from `reader`, one line at a time.
+pub fn for_each_game(reader: impl BufRead, mut f: impl FnMut(Game)) -> Result<(), ReadError> {
+    parse::try_fold_lines(reader, (), |(), line| parse_line(line).map(&mut f))
 }
 
-fn parse_sample(samples: &str) -> DiceCombination {
-    let samples = samples
-        .split(',')
-        .map(|sample| sample.trim())
-        .map(|sample| {
-            sample
-                .split_ascii_whitespace()
-                .collect_tuple::<(&str, &str)>()
-                .unwrap()
+/// The sum of the IDs of the possible games, reading one line at a time.
+pub fn sum_ids_of_possible_games_from_reader(
+    reader: impl BufRead,
+    dice_in_bag: &DiceCombination,
+) -> Result<u64, ReadError> {
+    parse::try_fold_lines(reader, 0u64, |sum, line| {
+        let game = parse_line(line)?;
+        Ok(match game.is_valid(dice_in_bag) {
+            true => sum + u64::from(game.id),
+            false => sum,
         })
-        .collect_vec();
+    })
+}
+
+/// The sum of the powers of the minimal sets, reading one line at a time.
+pub fn sum_power_of_minimum_dice_sets_from_reader(reader: impl BufRead) -> Result<u128, ReadError> {
+    // A power is below 2^96, so the sum only overflows after 2^32 games.
+    parse::try_fold_lines(reader, 0u128, |sum, line| {
+        Ok(sum + parse_line(line)?.get_minimal_set().power())
+    })
+}
+
+pub(crate) fn parse_line(line: Line) -> Result<Game, ParseError> {
+    let (id, samples) = line.label_and_id("Game")?;
+
+    Ok(Game {
+        id,
+        samples: parse_samples(line, samples)?,
+    })
+}
 
+fn parse_samples<'a>(
+    line: Line<'a>,
+    samples: Span<'a>,
+) -> Result<Vec<DiceCombination>, ParseError> {
+    samples
+        .split(';')
+        .map(|sample| parse_sample(line, sample))
+        .collect()
+}
+
+pub(crate) fn parse_sample<'a>(
+    line: Line<'a>,
+    samples: Span<'a>,
+) -> Result<DiceCombination, ParseError> {
     let mut combination = DiceCombination::new();
 
-    for (count, color) in samples {
-        match color {
-            "red" => combination.red = count.parse().unwrap(),
-            "green" => combination.green = count.parse().unwrap(),
-            "blue" => combination.blue = count.parse().unwrap(),
-            _ => panic!(),
+    for sample in samples.split(',').map(Span::trim) {
+        let (count, color) = sample
+            .split_whitespace()
+            .collect_tuple()
+            .ok_or_else(|| line.error(sample.start(), "expected '<count> <color>'"))?;
+        let count = line.integer(count)?;
+
+        match color.text() {
+            "red" => combination.red = count,
+            "green" => combination.green = count,
+            "blue" => combination.blue = count,
+            color_name => {
+                return Err(line.error(color.start(), format!("unknown color '{color_name}'")))
+            }
         }
     }
 
-    combination
+    Ok(combination)
 }
 
+/// The example from the puzzle description.
+pub(crate) const EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,6 +234,95 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
         );
     }
 
+    #[test]
+    fn from_reader() {
+        assert_eq!(
+            8,
+            sum_ids_of_possible_games_from_reader(EXAMPLE.as_bytes(), &RGB_DICE_COUNTS).unwrap()
+        );
+        assert_eq!(
+            2286,
+            sum_power_of_minimum_dice_sets_from_reader(EXAMPLE.as_bytes()).unwrap()
+        );
+
+        let mut ids = vec![];
+        for_each_game(EXAMPLE.as_bytes(), |game| ids.push(game.id())).unwrap();
+        assert_eq!(vec![1, 2, 3, 4, 5], ids);
+    }
+
+    #[test]
+    fn filter_example() {
+        let ids =
+            |expression| get_ids_of_games_matching(EXAMPLE, &Filter::parse(expression).unwrap());
+
+        assert_eq!(vec![3, 4], ids("red > 10"));
+        assert_eq!(vec![3], ids("red > 10 and blue < 15"));
+        assert_eq!(vec![3, 4, 5], ids("any sample has green >= 3 and red > 5"));
+        assert_eq!(vec![3, 4], ids("any sample has (green >= 3 and red >= 14)"));
+        assert_eq!(vec![1, 2, 3, 4], ids("samples count >= 3"));
+        assert_eq!(
+            vec![1, 2, 5],
+            ids("all samples have (red <= 12 and blue <= 14)")
+        );
+        assert_eq!(vec![2, 5], ids("not (id = 1 or power > 100)"));
+
+        let large = "Game 1: 5000000 red, 5000000 green, 5000000 blue\nGame 2: 5000000 red";
+        let ids =
+            |expression| get_ids_of_games_matching(large, &Filter::parse(expression).unwrap());
+        assert_eq!(vec![1], ids("power > 1"));
+        assert_eq!(vec![2], ids("not power > 1"));
+        assert_eq!(vec![2], ids("power <= 1"));
+        assert_eq!(vec![1, 2], ids("red >= 5000000"));
+    }
+
+    #[test]
+    fn filter_errors() {
+        assert_eq!(3, Filter::parse("red").unwrap_err().position);
+        assert_eq!(0, Filter::parse("purple > 1").unwrap_err().position);
+        assert_eq!(6, Filter::parse("red > blue").unwrap_err().position);
+        assert_eq!(8, Filter::parse("red > 1 ) ").unwrap_err().position);
+
+        let nested = format!("{}red > 1{}", "(".repeat(10_000), ")".repeat(10_000));
+        let error = Filter::parse(&nested).unwrap_err();
+        assert_eq!(
+            "expression is nested too deeply at position 200",
+            error.to_string()
+        );
+        let negated = format!("{}red > 1", "not ".repeat(10_000));
+        assert_eq!(800, Filter::parse(&negated).unwrap_err().position);
+        let chained = vec!["red > 1"; 10_000].join(" and ");
+        assert!(Filter::parse(&chained).is_err());
+        assert!(Filter::parse(&format!("{}red > 1{}", "(".repeat(100), ")".repeat(100))).is_ok());
+    }
+
+    #[test]
+    fn parse_games_example() {
+        let games = parse_games("Game 7: 3 blue, 4 red; 2 green").unwrap();
+        assert_eq!(7, games[0].id());
+        assert_eq!(2, games[0].samples().len());
+        assert_eq!(4, games[0].samples()[0].red());
+        assert_eq!(3, games[0].samples()[0].blue());
+        assert_eq!(2, games[0].samples()[1].green());
+
+        let error = parse_games("Game 1: 1 red\nGame 2: 3 purple").unwrap_err();
+        assert_eq!(2, error.line);
+        assert_eq!(11, error.column);
+        assert_eq!("unknown color 'purple'", error.message);
+    }
+
+    #[test]
+    fn export_example() {
+        let games = parse_games("Game 1: 3 blue, 4 red; 2 green\nGame 2: 1 red").unwrap();
+        assert_eq!(
+            r#"[{"id":1,"samples":[{"red":4,"green":0,"blue":3},{"red":0,"green":2,"blue":0}]},{"id":2,"samples":[{"red":1,"green":0,"blue":0}]}]"#,
+            games_to_json(&games)
+        );
+        assert_eq!(
+            "game,sample,red,green,blue\n1,1,4,0,3\n1,2,0,2,0\n2,1,1,0,0\n",
+            games_to_csv(&games)
+        );
+    }
+
     #[test]
     fn part_2_example() {
         let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
@@ -157,7 +334,18 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
             2286,
             get_power_of_minimum_dice_sets_per_game(input)
                 .iter()
-                .sum::<u32>()
+                .sum::<u128>()
+        );
+
+        let large =
+            "Game 1: 5000000 red, 5000000 green, 5000000 blue\nGame 2: 2 red, 3 green, 4 blue";
+        assert_eq!(
+            vec![125_000_000_000_000_000_000, 24],
+            get_power_of_minimum_dice_sets_per_game(large)
+        );
+        assert_eq!(
+            125_000_000_000_000_000_024,
+            sum_power_of_minimum_dice_sets_from_reader(large.as_bytes()).unwrap()
         );
     }
 
@@ -168,7 +356,7 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
             "The sum of powers of minimal sets of all games is {}",
             get_power_of_minimum_dice_sets_per_game(input)
                 .iter()
-                .sum::<u32>()
+                .sum::<u128>()
         );
     }
 }
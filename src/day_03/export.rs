@@ -0,0 +1,101 @@
+use super::Schematic;
+
+/// Renders the bipartite graph of numbers and symbols as Graphviz DOT.
+///
+/// Nodes are pinned to their grid coordinates (`x` = column, `y` = negated
+/// row), so `neato -n` reproduces the schematic layout.
+pub fn schematic_to_dot(schematic: &Schematic) -> String {
+    let mut dot = "graph schematic {\n".to_owned();
+
+    for (index, number) in schematic.numbers().iter().enumerate() {
+        dot.push_str(&format!(
+            "    n{index} [label=\"{}\", shape=box, pos=\"{},-{}!\"];\n",
+            number.value, number.col_span.start, number.row
+        ));
+    }
+    for (index, symbol) in schematic.symbols().iter().enumerate() {
+        dot.push_str(&format!(
+            "    s{index} [label=\"{}\", shape=circle, pos=\"{},-{}!\"];\n",
+            escape_dot(symbol.char),
+            symbol.pos.1,
+            symbol.pos.0
+        ));
+    }
+    for (index, symbol) in schematic.symbols().iter().enumerate() {
+        for number in symbol.adjacent_numbers.iter() {
+            dot.push_str(&format!("    s{index} -- n{number};\n"));
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn escape_dot(c: char) -> String {
+    match c {
+        '"' | '\\' => format!("\\{c}"),
+        _ => c.to_string(),
+    }
+}
+
+/// Renders the bipartite graph of numbers and symbols as GraphML, with the
+/// grid coordinates of every node stored in its `row` and `col` attributes.
+pub fn schematic_to_graphml(schematic: &Schematic) -> String {
+    let mut graphml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="kind" for="node" attr.name="kind" attr.type="string"/>
+  <key id="label" for="node" attr.name="label" attr.type="string"/>
+  <key id="row" for="node" attr.name="row" attr.type="int"/>
+  <key id="col" for="node" attr.name="col" attr.type="int"/>
+  <graph id="schematic" edgedefault="undirected">
+"#
+    .to_owned();
+
+    for (index, number) in schematic.numbers().iter().enumerate() {
+        graphml.push_str(&graphml_node(
+            &format!("n{index}"),
+            "number",
+            &number.value.to_string(),
+            (number.row, number.col_span.start),
+        ));
+    }
+    for (index, symbol) in schematic.symbols().iter().enumerate() {
+        graphml.push_str(&graphml_node(
+            &format!("s{index}"),
+            "symbol",
+            &escape_xml(symbol.char),
+            symbol.pos,
+        ));
+    }
+    for (index, symbol) in schematic.symbols().iter().enumerate() {
+        for number in symbol.adjacent_numbers.iter() {
+            graphml.push_str(&format!(
+                "    <edge source=\"s{index}\" target=\"n{number}\"/>\n"
+            ));
+        }
+    }
+
+    graphml.push_str("  </graph>\n</graphml>\n");
+    graphml
+}
+
+fn graphml_node(id: &str, kind: &str, label: &str, (row, col): (usize, usize)) -> String {
+    format!(
+        "    <node id=\"{id}\">\
+<data key=\"kind\">{kind}</data>\
+<data key=\"label\">{label}</data>\
+<data key=\"row\">{row}</data>\
+<data key=\"col\">{col}</data></node>\n"
+    )
+}
+
+pub(super) fn escape_xml(c: char) -> String {
+    match c {
+        '&' => "&amp;".to_owned(),
+        '<' => "&lt;".to_owned(),
+        '>' => "&gt;".to_owned(),
+        '"' => "&quot;".to_owned(),
+        '\'' => "&apos;".to_owned(),
+        _ => c.to_string(),
+    }
+}
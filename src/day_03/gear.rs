@@ -0,0 +1,103 @@
+use std::{collections::HashSet, ops::RangeInclusive};
+
+use itertools::Itertools;
+
+use super::{Schematic, Symbol};
+
+/// How the values of the numbers adjacent to a gear are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combine {
+    Product,
+    Sum,
+    Min,
+    Max,
+}
+
+impl Combine {
+    /// The combined value, or `None` if it overflows.
+    fn apply(self, values: impl Iterator<Item = u32>) -> Option<u64> {
+        let mut values = values.map(u64::from);
+        match self {
+            Combine::Product => values.try_fold(1u64, u64::checked_mul),
+            Combine::Sum => values.try_fold(0u64, u64::checked_add),
+            Combine::Min => Some(values.min().unwrap_or(0)),
+            Combine::Max => Some(values.max().unwrap_or(0)),
+        }
+    }
+}
+
+/// Decides which symbols count as gears and how their value is computed.
+///
+/// The default rule is the puzzle's: a `*` adjacent to exactly two numbers,
+/// valued at their product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    /// The symbols that may be gears; `None` accepts every symbol.
+    pub symbols: Option<HashSet<char>>,
+    pub adjacent_numbers: RangeInclusive<usize>,
+    pub combine: Combine,
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbols: Some(HashSet::from(['*'])),
+            adjacent_numbers: 2..=2,
+            combine: Combine::Product,
+        }
+    }
+}
+
+impl GearRule {
+    pub fn any_symbol(self) -> Self {
+        GearRule {
+            symbols: None,
+            ..self
+        }
+    }
+
+    pub fn with_symbols(self, symbols: &str) -> Self {
+        GearRule {
+            symbols: Some(symbols.chars().collect()),
+            ..self
+        }
+    }
+
+    pub fn with_adjacent_numbers(self, adjacent_numbers: RangeInclusive<usize>) -> Self {
+        GearRule {
+            adjacent_numbers,
+            ..self
+        }
+    }
+
+    pub fn with_combine(self, combine: Combine) -> Self {
+        GearRule { combine, ..self }
+    }
+
+    pub fn is_gear(&self, symbol: &Symbol) -> bool {
+        self.symbols
+            .as_ref()
+            .is_none_or(|symbols| symbols.contains(&symbol.char))
+            && self
+                .adjacent_numbers
+                .contains(&symbol.adjacent_numbers.len())
+    }
+}
+
+impl Schematic {
+    /// The symbols matching `rule`.
+    pub fn gears<'a>(&'a self, rule: &'a GearRule) -> impl Iterator<Item = &'a Symbol> {
+        self.symbols().iter().filter(|symbol| rule.is_gear(symbol))
+    }
+
+    /// The combined value of every gear matching `rule`, in reading order,
+    /// `None` where it overflows.
+    pub fn gear_values(&self, rule: &GearRule) -> Vec<Option<u64>> {
+        self.gears(rule)
+            .map(|gear| {
+                rule.combine
+                    .apply(self.adjacent_numbers(gear).map(|number| number.value))
+            })
+            .collect_vec()
+    }
+}
@@ -1,133 +1,54 @@
-use std::collections::HashSet;
-
-use array2d::Array2D;
 use itertools::Itertools;
 
-pub fn get_numbers_adjacent_to_symbols(input: &str) -> Vec<u32> {
-    let lines = input
-        .lines()
-        .map(str::chars)
-        .map(Itertools::collect_vec)
-        .collect_vec();
-    let schematic = Array2D::from_rows(&lines).unwrap();
-
-    let number_adresses: HashSet<(usize, usize)> = get_indices_of_symbols(&schematic)
-        .iter()
-        .flat_map(|&address| get_neighbouring_digits(&schematic, address))
-        .map(|address| get_start_index_of_number(&schematic, address))
-        .collect();
-
-    number_adresses
-        .iter()
-        .map(|&address| get_number_at(&schematic, address))
-        .collect_vec()
-}
+mod export;
+mod gear;
+mod render;
+mod schematic;
 
-pub fn get_gear_ratios(input: &str) -> Vec<u32> {
-    let lines = input
-        .lines()
-        .map(str::chars)
-        .map(Itertools::collect_vec)
-        .collect_vec();
-    let schematic = Array2D::from_rows(&lines).unwrap();
-
-    get_indices_of_symbols_with_predicate(&schematic, |c| c == '*')
-        .iter()
-        .map(|&address| get_neighbouring_digits(&schematic, address))
-        .map(|digits| {
-            digits
-                .iter()
-                .map(|&address| get_start_index_of_number(&schematic, address))
-                .collect_vec()
-        })
-        .map(|numbers| HashSet::from_iter(numbers.iter().cloned()))
-        .filter(|numbers| numbers.len() == 2)
-        .map(|numbers: HashSet<(usize, usize)>| {
-            numbers
-                .iter()
-                .map(|&address| get_number_at(&schematic, address))
-                .reduce(|acc, e| acc * e)
-                .unwrap()
-        })
-        .collect_vec()
-}
+pub use export::{schematic_to_dot, schematic_to_graphml};
+pub use gear::{Combine, GearRule};
+pub use render::{highlight_cells, render_schematic, Highlight, RenderFormat};
+pub use schematic::{Counting, PartNumber, Schematic, SchematicError, SchematicOptions, Symbol};
 
-fn get_number_at(schematic: &Array2D<char>, (row_index, col_index): (usize, usize)) -> u32 {
-    let mut col = col_index;
-    let mut number_as_string = "".to_owned();
-    while let Some(val) = schematic.get(row_index, col) {
-        if val.is_ascii_digit() {
-            number_as_string.push(*val);
-            col += 1;
-        } else {
-            break;
-        }
-    }
-    number_as_string.parse().unwrap()
+pub fn get_numbers_adjacent_to_symbols(input: &str) -> Vec<u32> {
+    get_numbers_adjacent_to_symbols_counting(input, Counting::UniqueByPosition)
 }
 
-fn get_indices_of_symbols(schematic: &Array2D<char>) -> Vec<(usize, usize)> {
-    get_indices_of_symbols_with_predicate(schematic, |c| !c.is_ascii_digit() && c != '.')
+pub fn get_numbers_adjacent_to_symbols_counting(input: &str, counting: Counting) -> Vec<u32> {
+    Schematic::parse(input)
+        .unwrap()
+        .part_number_values(counting)
 }
 
-fn get_indices_of_symbols_with_predicate<F>(
-    schematic: &Array2D<char>,
-    predicate: F,
-) -> Vec<(usize, usize)>
-where
-    F: Fn(char) -> bool,
-{
-    let mut symbol_indices = vec![];
-    for (row_index, row) in schematic.rows_iter().enumerate() {
-        for (col_index, &element) in row.enumerate() {
-            if predicate(element) {
-                symbol_indices.push((row_index, col_index));
-            }
-        }
-    }
-
-    symbol_indices
+pub fn get_gear_ratios(input: &str) -> Vec<u64> {
+    // The product of two `u32` part numbers always fits into a `u64`.
+    get_gear_values(input, &GearRule::default())
+        .into_iter()
+        .flatten()
+        .collect_vec()
 }
 
-fn get_neighbouring_digits(
-    schematic: &Array2D<char>,
-    (row_index, col_index): (usize, usize),
-) -> Vec<(usize, usize)> {
-    let mut neighbour_indices = vec![];
-
-    for row in 1.max(row_index) - 1..=row_index + 1 {
-        for col in 1.max(col_index) - 1..=col_index + 1 {
-            if let Some(&val) = schematic.get(row, col) {
-                if val.is_ascii_digit() {
-                    neighbour_indices.push((row, col));
-                }
-            }
-        }
-    }
-
-    neighbour_indices
+pub fn get_gear_values(input: &str, rule: &GearRule) -> Vec<Option<u64>> {
+    Schematic::parse(input).unwrap().gear_values(rule)
 }
 
-fn get_start_index_of_number(
-    schematic: &Array2D<char>,
-    (row_index, col_index): (usize, usize),
-) -> (usize, usize) {
-    let mut col = col_index;
-    while let Some(val) = schematic.get(row_index, col) {
-        if col == 0 && val.is_ascii_digit() {
-            return (row_index, col);
-        } else if val.is_ascii_digit() {
-            col -= 1;
-        } else {
-            return (row_index, col + 1);
-        }
-    }
-    panic!()
-}
+/// The example from the puzzle description.
+pub(crate) const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::grid::{Grid, GridError};
 
     #[test]
     fn part_1_example() {
@@ -156,6 +77,191 @@ mod tests {
         );
     }
 
+    #[test]
+    fn schematic_relationships() {
+        let schematic = Schematic::parse(
+            "467..114..
+...*......
+..35..633.
+......#...",
+        )
+        .unwrap();
+
+        let numbers = schematic.numbers();
+        assert_eq!(4, numbers.len());
+        assert_eq!(114, numbers[1].value);
+        assert_eq!(5..8, numbers[1].col_span);
+        assert!(!numbers[1].is_part_number());
+        assert_eq!(vec![0], numbers[2].adjacent_symbols);
+        assert_eq!(vec![1], numbers[3].adjacent_symbols);
+
+        let gear = &schematic.symbols()[0];
+        assert_eq!(('*', (1, 3)), (gear.char, gear.pos));
+        assert_eq!(
+            vec![467, 35],
+            schematic
+                .adjacent_numbers(gear)
+                .map(|number| number.value)
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn counting_at_edges() {
+        let input = "12......34
+*.......$#
+..5*5.....
+......11.7
+8+.......#";
+        let sorted_values = |counting| {
+            get_numbers_adjacent_to_symbols_counting(input, counting)
+                .into_iter()
+                .sorted()
+                .collect_vec()
+        };
+
+        assert_eq!(
+            vec![5, 5, 7, 8, 12, 34],
+            sorted_values(Counting::UniqueByPosition)
+        );
+        assert_eq!(
+            vec![5, 7, 8, 12, 34],
+            sorted_values(Counting::UniqueByValue)
+        );
+        assert_eq!(
+            vec![5, 5, 7, 8, 12, 34, 34],
+            sorted_values(Counting::PerSymbolAdjacency)
+        );
+    }
+
+    #[test]
+    fn numbers_in_column_zero() {
+        let input = "1.
+*.
+23
+.#";
+        let schematic = Schematic::parse(input).unwrap();
+        assert_eq!(
+            vec![(0, 0..1), (2, 0..2)],
+            schematic
+                .numbers()
+                .iter()
+                .map(|number| (number.row, number.col_span.clone()))
+                .collect_vec()
+        );
+        assert_eq!(
+            vec![1, 23],
+            get_numbers_adjacent_to_symbols_counting(input, Counting::UniqueByPosition)
+        );
+        assert_eq!(
+            vec![1, 23, 23],
+            get_numbers_adjacent_to_symbols_counting(input, Counting::PerSymbolAdjacency)
+        );
+    }
+
+    #[test]
+    fn graph_export() {
+        let schematic = Schematic::parse(
+            "12.
+.&.
+..3",
+        )
+        .unwrap();
+
+        assert_eq!(
+            r#"graph schematic {
+    n0 [label="12", shape=box, pos="0,-0!"];
+    n1 [label="3", shape=box, pos="2,-2!"];
+    s0 [label="&", shape=circle, pos="1,-1!"];
+    s0 -- n0;
+    s0 -- n1;
+}
+"#,
+            schematic_to_dot(&schematic)
+        );
+
+        let graphml = schematic_to_graphml(&schematic);
+        assert!(graphml.contains(
+            r#"<node id="s0"><data key="kind">symbol</data><data key="label">&amp;</data><data key="row">1</data><data key="col">1</data></node>"#
+        ));
+        assert!(graphml.contains(r#"<edge source="s0" target="n1"/>"#));
+    }
+
+    #[test]
+    fn render_highlights() {
+        let input = "12.4
+*..#
+..&.";
+        let grid = Grid::parse(input).unwrap();
+        let schematic = Schematic::from_grid(&grid).unwrap();
+        let rule = GearRule::default()
+            .any_symbol()
+            .with_adjacent_numbers(1..=1);
+
+        assert_eq!(
+            "\x1b[32m12\x1b[0m.\x1b[32m4\x1b[0m\n\x1b[33m*\x1b[0m..\x1b[33m#\x1b[0m\n..\x1b[34m&\x1b[0m.\n",
+            render_schematic(&grid, &schematic, &rule, RenderFormat::Ansi)
+        );
+        assert!(
+            render_schematic(&grid, &schematic, &rule, RenderFormat::Html)
+                .contains("<span style=\"color: blue\">&amp;</span>")
+        );
+
+        let loose = Schematic::parse("7.").unwrap();
+        assert_eq!(
+            Some(&Highlight::LooseNumber),
+            highlight_cells(&loose, &rule).get(&(0, 0))
+        );
+    }
+
+    #[test]
+    fn robust_parsing() {
+        let ragged = "467..114\r\n...*......\r\n..35..633.\r\n";
+        assert_eq!(
+            Err(SchematicError::Grid(GridError::RaggedRow {
+                line: 2,
+                expected: 8,
+                found: 10
+            })),
+            Schematic::parse(ragged)
+        );
+
+        let options = SchematicOptions::default().with_padded_rows();
+        let schematic = Schematic::parse_with(ragged, &options).unwrap();
+        assert_eq!(
+            vec![467, 35],
+            schematic
+                .part_numbers()
+                .map(|number| number.value)
+                .collect_vec()
+        );
+
+        let spaced = "12  λ\n  é 3";
+        let options = SchematicOptions::default().with_empty(' ');
+        assert_eq!(
+            vec![12, 3],
+            Schematic::parse_with(spaced, &options)
+                .unwrap()
+                .part_numbers()
+                .map(|number| number.value)
+                .collect_vec()
+        );
+
+        let options = options.with_symbols("λ");
+        let schematic = Schematic::parse_with(spaced, &options).unwrap();
+        assert_eq!(
+            vec!['λ'],
+            schematic.symbols().iter().map(|s| s.char).collect_vec()
+        );
+        assert_eq!(
+            vec![3],
+            schematic
+                .part_numbers()
+                .map(|number| number.value)
+                .collect_vec()
+        );
+    }
+
     #[test]
     fn part_2_example() {
         let input = "467..114..
@@ -168,7 +274,46 @@ mod tests {
 ......755.
 ...$.*....
 .664.598..";
-        assert_eq!(467835, get_gear_ratios(input).iter().sum::<u32>())
+        assert_eq!(467835, get_gear_ratios(input).iter().sum::<u64>())
+    }
+
+    #[test]
+    fn gear_rules() {
+        assert_eq!(
+            vec![Some(467 + 35), Some(755 + 598)],
+            get_gear_values(EXAMPLE, &GearRule::default().with_combine(Combine::Sum))
+        );
+        assert_eq!(
+            [35, 633, 617, 592, 664, 598].map(Some).to_vec(),
+            get_gear_values(
+                EXAMPLE,
+                &GearRule::default()
+                    .any_symbol()
+                    .with_adjacent_numbers(1..=3)
+                    .with_combine(Combine::Min)
+            )
+        );
+        assert_eq!(
+            [467, 617, 664, 755].map(Some).to_vec(),
+            get_gear_values(
+                EXAMPLE,
+                &GearRule::default()
+                    .with_symbols("*$")
+                    .with_adjacent_numbers(1..=2)
+                    .with_combine(Combine::Max)
+            )
+        );
+        assert!(
+            get_gear_values(EXAMPLE, &GearRule::default().with_adjacent_numbers(3..=3)).is_empty()
+        );
+
+        let large = "4000000000*4000000000\n.........4000000000..";
+        let three = GearRule::default().with_adjacent_numbers(3..=3);
+        assert_eq!(vec![None], get_gear_values(large, &three));
+        assert_eq!(
+            vec![Some(12_000_000_000)],
+            get_gear_values(large, &three.with_combine(Combine::Sum))
+        );
     }
 
     #[test]
@@ -176,7 +321,7 @@ mod tests {
         let input = include_str!("input.txt");
         println!(
             "The sum of all gear ratios is {}",
-            get_gear_ratios(input).iter().sum::<u32>()
+            get_gear_ratios(input).iter().sum::<u64>()
         );
     }
 }
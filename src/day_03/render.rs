@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use super::{export::escape_xml, GearRule, Schematic};
+use crate::grid::{Grid, Position};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderFormat {
+    Ansi,
+    Html,
+    Svg,
+}
+
+/// The role of a schematic cell, which decides its colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Highlight {
+    PartNumber,
+    LooseNumber,
+    Gear,
+    Symbol,
+    Empty,
+}
+
+impl Highlight {
+    fn ansi_code(self) -> Option<&'static str> {
+        match self {
+            Highlight::PartNumber => Some("32"),
+            Highlight::LooseNumber => Some("31"),
+            Highlight::Gear => Some("33"),
+            Highlight::Symbol => Some("34"),
+            Highlight::Empty => None,
+        }
+    }
+
+    fn colour(self) -> &'static str {
+        match self {
+            Highlight::PartNumber => "green",
+            Highlight::LooseNumber => "red",
+            Highlight::Gear => "goldenrod",
+            Highlight::Symbol => "blue",
+            Highlight::Empty => "gray",
+        }
+    }
+}
+
+/// Classifies the number and symbol cells of `schematic` by their
+/// adjacency; gears are the symbols matching `rule`.
+pub fn highlight_cells(schematic: &Schematic, rule: &GearRule) -> HashMap<Position, Highlight> {
+    let mut highlights = HashMap::new();
+
+    for number in schematic.numbers() {
+        let highlight = if number.is_part_number() {
+            Highlight::PartNumber
+        } else {
+            Highlight::LooseNumber
+        };
+        for col in number.col_span.clone() {
+            highlights.insert((number.row, col), highlight);
+        }
+    }
+    for symbol in schematic.symbols() {
+        let highlight = if rule.is_gear(symbol) {
+            Highlight::Gear
+        } else {
+            Highlight::Symbol
+        };
+        highlights.insert(symbol.pos, highlight);
+    }
+
+    highlights
+}
+
+pub fn render_schematic(
+    grid: &Grid<char>,
+    schematic: &Schematic,
+    rule: &GearRule,
+    format: RenderFormat,
+) -> String {
+    let highlights = highlight_cells(schematic, rule);
+    let highlight_of = |position| {
+        highlights
+            .get(&position)
+            .copied()
+            .unwrap_or(Highlight::Empty)
+    };
+
+    match format {
+        RenderFormat::Ansi => render_ansi(grid, highlight_of),
+        RenderFormat::Html => render_html(grid, highlight_of),
+        RenderFormat::Svg => render_svg(grid, highlight_of),
+    }
+}
+
+fn render_ansi(grid: &Grid<char>, highlight_of: impl Fn(Position) -> Highlight) -> String {
+    let mut output = String::new();
+
+    for row in 0..grid.num_rows() {
+        for (highlight, cells) in grid
+            .row(row)
+            .group_by(|&(position, _)| highlight_of(position))
+            .into_iter()
+        {
+            let text = cells.map(|(_, c)| c).collect::<String>();
+            match highlight.ansi_code() {
+                Some(code) => output.push_str(&format!("\x1b[{code}m{text}\x1b[0m")),
+                None => output.push_str(&text),
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn render_html(grid: &Grid<char>, highlight_of: impl Fn(Position) -> Highlight) -> String {
+    let mut output = "<pre class=\"schematic\">".to_owned();
+
+    for row in 0..grid.num_rows() {
+        for (highlight, cells) in grid
+            .row(row)
+            .group_by(|&(position, _)| highlight_of(position))
+            .into_iter()
+        {
+            let text = cells.map(|(_, &c)| escape_xml(c)).collect::<String>();
+            match highlight {
+                Highlight::Empty => output.push_str(&text),
+                _ => output.push_str(&format!(
+                    "<span style=\"color: {}\">{text}</span>",
+                    highlight.colour()
+                )),
+            }
+        }
+        output.push('\n');
+    }
+
+    output.push_str("</pre>\n");
+    output
+}
+
+const SVG_CELL_WIDTH: usize = 10;
+const SVG_CELL_HEIGHT: usize = 16;
+
+fn render_svg(grid: &Grid<char>, highlight_of: impl Fn(Position) -> Highlight) -> String {
+    let mut output = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+font-family=\"monospace\" font-size=\"14\">\n",
+        grid.num_columns() * SVG_CELL_WIDTH,
+        grid.num_rows() * SVG_CELL_HEIGHT
+    );
+
+    for ((row, col), &c) in grid.iter() {
+        let highlight = highlight_of((row, col));
+        output.push_str(&format!(
+            "  <text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>\n",
+            col * SVG_CELL_WIDTH,
+            (row + 1) * SVG_CELL_HEIGHT - 4,
+            highlight.colour(),
+            escape_xml(c)
+        ));
+    }
+
+    output.push_str("</svg>\n");
+    output
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+    ops::Range,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    grid::{Grid, GridError, Position},
+    parse::ParseError,
+};
+
+/// A run of digits in the schematic. `adjacent_symbols` holds indices into
+/// [`Schematic::symbols`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartNumber {
+    pub value: u32,
+    pub row: usize,
+    pub col_span: Range<usize>,
+    pub adjacent_symbols: Vec<usize>,
+}
+
+impl PartNumber {
+    /// Whether the number touches at least one symbol.
+    pub fn is_part_number(&self) -> bool {
+        !self.adjacent_symbols.is_empty()
+    }
+}
+
+/// A symbol cell in the schematic. `adjacent_numbers` holds indices into
+/// [`Schematic::numbers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub char: char,
+    pub pos: Position,
+    pub adjacent_numbers: Vec<usize>,
+}
+
+/// How part numbers touching several symbols, or sharing a value with other
+/// part numbers, are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counting {
+    /// Every number touching a symbol counts once, regardless of how many
+    /// symbols it touches.
+    UniqueByPosition,
+    /// Numbers touching a symbol count once per distinct value.
+    UniqueByValue,
+    /// Numbers count once for every symbol they touch.
+    PerSymbolAdjacency,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchematicError {
+    Grid(GridError),
+    Parse(ParseError),
+}
+
+impl fmt::Display for SchematicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchematicError::Grid(error) => write!(f, "{error}"),
+            SchematicError::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for SchematicError {}
+
+impl From<GridError> for SchematicError {
+    fn from(error: GridError) -> Self {
+        SchematicError::Grid(error)
+    }
+}
+
+/// Controls how the text of a schematic is turned into numbers and symbols.
+///
+/// By default `.` marks an empty cell, every other non-digit is a symbol and
+/// ragged rows are rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchematicOptions {
+    pub empty: char,
+    /// The characters counted as symbols; `None` counts every character that
+    /// is neither an ASCII digit nor `empty`.
+    pub symbols: Option<HashSet<char>>,
+    /// Pad rows shorter than the longest one with `empty` instead of
+    /// reporting them as an error.
+    pub pad_ragged_rows: bool,
+}
+
+impl Default for SchematicOptions {
+    fn default() -> Self {
+        SchematicOptions {
+            empty: '.',
+            symbols: None,
+            pad_ragged_rows: false,
+        }
+    }
+}
+
+impl SchematicOptions {
+    pub fn with_empty(self, empty: char) -> Self {
+        SchematicOptions { empty, ..self }
+    }
+
+    pub fn with_symbols(self, symbols: &str) -> Self {
+        SchematicOptions {
+            symbols: Some(symbols.chars().collect()),
+            ..self
+        }
+    }
+
+    pub fn with_padded_rows(self) -> Self {
+        SchematicOptions {
+            pad_ragged_rows: true,
+            ..self
+        }
+    }
+
+    pub fn parse_grid(&self, input: &str) -> Result<Grid<char>, GridError> {
+        if self.pad_ragged_rows {
+            Ok(Grid::parse_padded(input, self.empty))
+        } else {
+            Grid::parse(input)
+        }
+    }
+
+    pub fn is_symbol(&self, c: char) -> bool {
+        match &self.symbols {
+            Some(symbols) => symbols.contains(&c),
+            None => !c.is_ascii_digit() && c != self.empty,
+        }
+    }
+}
+
+/// The numbers and symbols of an engine schematic together with their
+/// adjacency, computed in a single pass over the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schematic {
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
+}
+
+impl Schematic {
+    pub fn parse(input: &str) -> Result<Self, SchematicError> {
+        Schematic::parse_with(input, &SchematicOptions::default())
+    }
+
+    pub fn parse_with(input: &str, options: &SchematicOptions) -> Result<Self, SchematicError> {
+        Schematic::from_grid_with(&options.parse_grid(input)?, options)
+    }
+
+    pub fn from_grid(grid: &Grid<char>) -> Result<Self, SchematicError> {
+        Schematic::from_grid_with(grid, &SchematicOptions::default())
+    }
+
+    pub fn from_grid_with(
+        grid: &Grid<char>,
+        options: &SchematicOptions,
+    ) -> Result<Self, SchematicError> {
+        let mut numbers = vec![];
+        let mut number_at: HashMap<Position, usize> = HashMap::new();
+
+        for row in 0..grid.num_rows() {
+            let digit_runs = grid
+                .row(row)
+                .filter(|(_, c)| c.is_ascii_digit())
+                .map(|((_, col), _)| col)
+                .enumerate()
+                .group_by(|&(index, col)| col - index);
+
+            for (_, run) in digit_runs.into_iter() {
+                let cols = run.map(|(_, col)| col).collect_vec();
+                let col_span = cols[0]..cols[cols.len() - 1] + 1;
+                let value = cols
+                    .iter()
+                    .map(|&col| grid.get((row, col)).unwrap().to_digit(10).unwrap())
+                    .try_fold(0u32, |value, digit| {
+                        value.checked_mul(10)?.checked_add(digit)
+                    })
+                    .ok_or_else(|| {
+                        SchematicError::Parse(ParseError {
+                            line: row + 1,
+                            column: col_span.start + 1,
+                            message: "number is too large".to_owned(),
+                        })
+                    })?;
+
+                for &col in cols.iter() {
+                    number_at.insert((row, col), numbers.len());
+                }
+                numbers.push(PartNumber {
+                    value,
+                    row,
+                    col_span,
+                    adjacent_symbols: vec![],
+                });
+            }
+        }
+
+        let mut symbols = vec![];
+        for pos in grid.find_all(|&c| options.is_symbol(c)) {
+            let adjacent_numbers = grid
+                .neighbours8(pos)
+                .filter_map(|pos| number_at.get(&pos).copied())
+                .sorted()
+                .dedup()
+                .collect_vec();
+
+            for &number in adjacent_numbers.iter() {
+                numbers[number].adjacent_symbols.push(symbols.len());
+            }
+            symbols.push(Symbol {
+                char: *grid.get(pos).unwrap(),
+                pos,
+                adjacent_numbers,
+            });
+        }
+
+        Ok(Schematic { numbers, symbols })
+    }
+
+    pub fn numbers(&self) -> &[PartNumber] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// The numbers adjacent to at least one symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers.iter().filter(|number| number.is_part_number())
+    }
+
+    pub fn part_number_values(&self, counting: Counting) -> Vec<u32> {
+        match counting {
+            Counting::UniqueByPosition => {
+                self.part_numbers().map(|number| number.value).collect_vec()
+            }
+            Counting::UniqueByValue => self
+                .part_numbers()
+                .map(|number| number.value)
+                .unique()
+                .collect_vec(),
+            Counting::PerSymbolAdjacency => self
+                .symbols
+                .iter()
+                .flat_map(|symbol| self.adjacent_numbers(symbol))
+                .map(|number| number.value)
+                .collect_vec(),
+        }
+    }
+
+    pub fn adjacent_numbers<'a>(
+        &'a self,
+        symbol: &'a Symbol,
+    ) -> impl Iterator<Item = &'a PartNumber> {
+        symbol
+            .adjacent_numbers
+            .iter()
+            .map(|&index| &self.numbers[index])
+    }
+
+    pub fn adjacent_symbols<'a>(
+        &'a self,
+        number: &'a PartNumber,
+    ) -> impl Iterator<Item = &'a Symbol> {
+        number
+            .adjacent_symbols
+            .iter()
+            .map(|&index| &self.symbols[index])
+    }
+}
@@ -1,102 +1,399 @@
+use std::{collections::VecDeque, error::Error, fmt, io::BufRead};
+
 use itertools::Itertools;
-use std::{collections::HashMap, hash::Hash};
 
-#[derive(Eq)]
-struct Card {
+use crate::{
+    parallel,
+    parse::{self, Line, ParseError, ReadError, Span},
+};
+
+mod number_set;
+mod rules;
+mod trace;
+
+pub use number_set::NumberSet;
+pub use rules::{CopyRule, Scoring};
+pub use trace::{CardTrace, CascadeTrace};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
     id: u32,
+    winning_numbers: NumberSet,
+    scratched_numbers: NumberSet,
     win_count: u32,
 }
 
 impl Card {
-    fn new(id: u32, winning_numbers: Vec<u32>, scratched_numbers: Vec<u32>) -> Self {
+    pub fn new(id: u32, winning_numbers: NumberSet, scratched_numbers: NumberSet) -> Self {
+        let win_count = winning_numbers.intersection(&scratched_numbers).len() as u32;
         Card {
             id,
-            win_count: scratched_numbers
-                .iter()
-                .filter(|&scratched_number| winning_numbers.contains(scratched_number))
-                .count() as u32,
+            winning_numbers,
+            scratched_numbers,
+            win_count,
         }
     }
 
-    fn get_score(self) -> u32 {
-        2u32.pow(self.win_count) / 2
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn winning_numbers(&self) -> &NumberSet {
+        &self.winning_numbers
+    }
+
+    pub fn scratched_numbers(&self) -> &NumberSet {
+        &self.scratched_numbers
+    }
+
+    /// The scratched numbers that are also winning numbers.
+    pub fn matching_numbers(&self) -> NumberSet {
+        self.winning_numbers.intersection(&self.scratched_numbers)
+    }
+
+    pub fn win_count(&self) -> u32 {
+        self.win_count
     }
-}
 
-impl Hash for Card {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.id.hash(state);
+    pub fn get_score(&self, scoring: &Scoring) -> Result<u128, CardError> {
+        scoring
+            .score(self.win_count)
+            .ok_or(CardError::ScoreOverflow { card: self.id })
     }
 }
 
-impl PartialEq for Card {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardError {
+    Parse(ParseError),
+    /// Card IDs must count up from 1 without gaps, as copies are awarded to
+    /// the cards following a winning card.
+    NonContiguousId {
+        line: usize,
+        expected: u32,
+        found: u32,
+    },
+    CountOverflow,
+    ScoreOverflow {
+        card: u32,
+    },
+    ScoreSumOverflow,
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardError::Parse(error) => write!(f, "{error}"),
+            CardError::NonContiguousId {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: expected card {expected} but found card {found}"
+            ),
+            CardError::CountOverflow => write!(f, "the number of card copies overflows"),
+            CardError::ScoreOverflow { card } => write!(f, "the score of card {card} overflows"),
+            CardError::ScoreSumOverflow => write!(f, "the sum of the card scores overflows"),
+        }
     }
 }
 
+impl Error for CardError {}
+
+/// An unsigned integer wide enough to hold the number of card copies, which
+/// roughly doubles with every winning card.
+pub trait CardCount: Copy + Default + From<u32> {
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_card_count {
+    ($($t:ty),*) => {
+        $(
+            impl CardCount for $t {
+                const ONE: Self = 1;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_card_count!(u32, u64, u128);
+
 pub fn get_points_from_cards(input: &str) -> Vec<u32> {
-    input
-        .lines()
-        .map(parse_line)
-        .map(Card::get_score)
+    get_scores_from_cards(input, &Scoring::Doubling)
+        .unwrap()
+        .into_iter()
+        .map(|score| u32::try_from(score).unwrap())
         .collect_vec()
 }
 
+pub fn get_scores_from_cards(input: &str, scoring: &Scoring) -> Result<Vec<u128>, CardError> {
+    let lines = parse::lines(input).collect_vec();
+    parallel::map(&lines, |&line| parse_line(line)?.get_score(scoring))
+        .into_iter()
+        .collect()
+}
+
 pub fn count_copies_of_cards(input: &str) -> u32 {
-    let cards = input.lines().map(parse_line).collect_vec();
-    let mut copies_per_card: HashMap<&Card, u32> = HashMap::new();
+    count_copies(input).unwrap()
+}
+
+/// Counts the cards held once every won copy has been processed.
+pub fn count_copies<C: CardCount>(input: &str) -> Result<C, CardError> {
+    count_copies_with(input, &CopyRule::default())
+}
+
+pub fn count_copies_with<C: CardCount>(input: &str, rule: &CopyRule) -> Result<C, CardError> {
+    let cards = parse_cards(input)?;
+    copies_per_card::<C>(&cards, rule)?
+        .into_iter()
+        .try_fold(C::default(), C::checked_add)
+        .ok_or(CardError::CountOverflow)
+}
+
+/// The sum of the card scores, reading one line at a time.
+pub fn sum_scores_from_reader(
+    reader: impl BufRead,
+    scoring: &Scoring,
+) -> Result<u128, ReadError<CardError>> {
+    parse::try_fold_lines(reader, 0u128, |sum, line| {
+        sum.checked_add(parse_line(line)?.get_score(scoring)?)
+            .ok_or(CardError::ScoreSumOverflow)
+    })
+}
+
+/// Like [`count_copies`], but reads one line at a time. Only the copies
+/// still owed to upcoming cards are kept, so memory is bounded by the
+/// largest number of matches on a card rather than by the table size.
+pub fn count_copies_from_reader<C: CardCount>(
+    reader: impl BufRead,
+) -> Result<C, ReadError<CardError>> {
+    let mut owed: VecDeque<C> = VecDeque::new();
+
+    let (_, total) =
+        parse::try_fold_lines(reader, (0u32, C::default()), |(cards, total), line| {
+            let card = parse_line(line)?;
+            let expected = cards + 1;
+            if card.id != expected {
+                return Err(CardError::NonContiguousId {
+                    line: line.number(),
+                    expected,
+                    found: card.id,
+                });
+            }
+
+            let count = owed
+                .pop_front()
+                .unwrap_or_default()
+                .checked_add(C::ONE)
+                .ok_or(CardError::CountOverflow)?;
+            let targets = card.win_count as usize;
+            if owed.len() < targets {
+                owed.resize(targets, C::default());
+            }
+            for copies in owed.iter_mut().take(targets) {
+                *copies = copies.checked_add(count).ok_or(CardError::CountOverflow)?;
+            }
 
-    for card in cards.iter() {
-        copies_per_card.insert(card, 1);
+            let total = total.checked_add(count).ok_or(CardError::CountOverflow)?;
+            Ok((expected, total))
+        })?;
+
+    Ok(total)
+}
+
+/// Computes the copies of every card in one pass. A winning card adds the
+/// copies it awards to a running total and schedules the same amount to be
+/// removed again after its last awarded card. Awards wrapping around to the
+/// top of the table are accumulated the same way and added at the end.
+fn copies_per_card<C: CardCount>(cards: &[Card], rule: &CopyRule) -> Result<Vec<C>, CardError> {
+    let copies_per_match = C::from(rule.copies_per_match);
+    let mut expiring = vec![C::default(); cards.len() + 1];
+    let mut won = C::default();
+    let mut wrapped_expiring = vec![C::default(); cards.len() + 1];
+    let mut wrapped = C::default();
+    let mut copies = Vec::with_capacity(cards.len());
+
+    for (index, card) in cards.iter().enumerate() {
+        won = won
+            .checked_sub(expiring[index])
+            .ok_or(CardError::CountOverflow)?;
+        let count = won.checked_add(C::ONE).ok_or(CardError::CountOverflow)?;
+        copies.push(count);
+
+        let awarded = count
+            .checked_mul(copies_per_match)
+            .ok_or(CardError::CountOverflow)?;
+        let targets = if rule.count_only_wrap {
+            (card.win_count as usize).min(cards.len() - 1)
+        } else {
+            card.win_count as usize
+        };
+        let end = (index + 1 + targets).min(cards.len());
+        if index + 1 < end {
+            won = won.checked_add(awarded).ok_or(CardError::CountOverflow)?;
+            expiring[end] = expiring[end]
+                .checked_add(awarded)
+                .ok_or(CardError::CountOverflow)?;
+        }
+
+        let wrapped_end = (index + 1 + targets).saturating_sub(cards.len());
+        if rule.count_only_wrap && wrapped_end > 0 {
+            wrapped = wrapped
+                .checked_add(awarded)
+                .ok_or(CardError::CountOverflow)?;
+            wrapped_expiring[wrapped_end] = wrapped_expiring[wrapped_end]
+                .checked_add(awarded)
+                .ok_or(CardError::CountOverflow)?;
+        }
     }
 
-    for card in cards.iter() {
-        for card_to_update in cards
-            .iter()
-            .skip(card.id as usize)
-            .take(card.win_count as usize)
-        {
-            *copies_per_card.entry(card_to_update).or_insert(1) +=
-                *copies_per_card.entry(card).or_default();
+    for (index, count) in copies.iter_mut().enumerate() {
+        wrapped = wrapped
+            .checked_sub(wrapped_expiring[index])
+            .ok_or(CardError::CountOverflow)?;
+        *count = count.checked_add(wrapped).ok_or(CardError::CountOverflow)?;
+    }
+
+    Ok(copies)
+}
+
+/// Parses a table of cards, whose IDs must count up from 1.
+pub fn parse_cards(input: &str) -> Result<Vec<Card>, CardError> {
+    let mut cards = vec![];
+
+    for (index, line) in parse::lines(input).enumerate() {
+        let card = parse_line(line)?;
+        let expected = index as u32 + 1;
+        if card.id != expected {
+            return Err(CardError::NonContiguousId {
+                line: line.number(),
+                expected,
+                found: card.id,
+            });
         }
+        cards.push(card);
     }
 
-    copies_per_card.values().sum::<u32>()
+    Ok(cards)
 }
 
-fn parse_line(line: &str) -> Card {
-    let split_by_colon = line.split(':').collect_tuple::<(&str, &str)>().unwrap();
-    let game_id: &str = split_by_colon
-        .0
-        .split_ascii_whitespace()
-        .collect_tuple::<(&str, &str)>()
-        .unwrap()
-        .1;
+impl From<ParseError> for CardError {
+    fn from(error: ParseError) -> Self {
+        CardError::Parse(error)
+    }
+}
 
-    let (winning_numbers, scratched_numbers) = parse_numbers(split_by_colon.1);
+pub(crate) fn parse_line(line: Line) -> Result<Card, ParseError> {
+    let (id, numbers) = line.label_and_id("Card")?;
+    let (winning_numbers, scratched_numbers) = line.pair(numbers, '|')?;
 
-    Card::new(game_id.parse().unwrap(), winning_numbers, scratched_numbers)
+    Ok(Card::new(
+        id,
+        parse_number_set(line, winning_numbers, "winning")?,
+        parse_number_set(line, scratched_numbers, "scratched")?,
+    ))
 }
 
-fn parse_numbers(line: &str) -> (Vec<u32>, Vec<u32>) {
-    let (winning_numbers, scratched_numbers) =
-        line.split('|').collect_tuple::<(&str, &str)>().unwrap();
-    (
-        winning_numbers
-            .split_ascii_whitespace()
-            .filter_map(|number| number.parse().ok())
-            .collect_vec(),
-        scratched_numbers
-            .split_ascii_whitespace()
-            .filter_map(|number| number.parse().ok())
-            .collect_vec(),
-    )
+fn parse_number_set<'a>(
+    line: Line<'a>,
+    numbers: Span<'a>,
+    kind: &str,
+) -> Result<NumberSet, ParseError> {
+    let mut set = NumberSet::new();
+
+    for number in numbers.split_whitespace() {
+        if !set.insert(line.integer(number)?) {
+            return Err(line.error(
+                number.start(),
+                format!("duplicate {kind} number {}", number.text()),
+            ));
+        }
+    }
+
+    Ok(set)
 }
 
+/// The example from the puzzle description.
+pub(crate) const EXAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, Rng};
+    use proptest::prelude::*;
+
+    /// Hands out the copies won by each card to every target in turn, which
+    /// takes quadratic time but never loops once per copy.
+    fn count_copies_per_target(cards: &[Card], rule: &CopyRule) -> u128 {
+        let mut copies = vec![1u128; cards.len()];
+        let mut wrapped = vec![0u128; cards.len()];
+
+        for (index, card) in cards.iter().enumerate() {
+            let targets = match rule.count_only_wrap {
+                true => (card.win_count as usize).min(cards.len() - 1),
+                false => card.win_count as usize,
+            };
+            let awarded = copies[index] * u128::from(rule.copies_per_match);
+            for target in index + 1..=index + targets {
+                if target < cards.len() {
+                    copies[target] += awarded;
+                } else if rule.count_only_wrap {
+                    wrapped[target - cards.len()] += awarded;
+                }
+            }
+        }
+
+        copies.iter().chain(wrapped.iter()).sum()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn linear_copies_match_per_target(
+            seed in any::<u64>(),
+            cards in 0..20usize,
+            copies_per_match in 1..=2u32,
+            count_only_wrap in any::<bool>(),
+        ) {
+            let input = generate::scratchcards(&mut Rng::new(seed), cards, 10, 25);
+            let mut rule = CopyRule::default().with_copies_per_match(copies_per_match);
+            if count_only_wrap {
+                rule = rule.with_count_only_wrap();
+            }
+
+            let expected = count_copies_per_target(&parse_cards(&input).unwrap(), &rule);
+            prop_assert_eq!(expected, count_copies_with::<u128>(&input, &rule).unwrap(), "{}", input);
+            if rule == CopyRule::default() {
+                prop_assert_eq!(
+                    expected,
+                    count_copies_from_reader::<u128>(input.as_bytes()).unwrap()
+                );
+            }
+        }
+    }
 
     #[test]
     fn part_1_example() {
@@ -118,6 +415,117 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
         );
     }
 
+    #[test]
+    fn from_reader() {
+        let input = EXAMPLE;
+        assert_eq!(
+            13,
+            sum_scores_from_reader(input.as_bytes(), &Scoring::Doubling).unwrap()
+        );
+        assert_eq!(
+            30,
+            count_copies_from_reader::<u32>(input.as_bytes()).unwrap()
+        );
+
+        let error = sum_scores_from_reader(
+            "Card 1: 1 | 2\nCard 2: 1 | 2".as_bytes(),
+            &Scoring::Custom(Box::new(|_| Some(u128::MAX))),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ReadError::Input(CardError::ScoreSumOverflow)
+        ));
+        assert_eq!("the sum of the card scores overflows", error.to_string());
+
+        let real = include_str!("input.txt");
+        assert_eq!(
+            count_copies::<u64>(real).unwrap(),
+            count_copies_from_reader::<u64>(real.as_bytes()).unwrap()
+        );
+
+        assert!(matches!(
+            count_copies_from_reader::<u32>("Card 1: 1 | 2\nCard 3: 1 | 2".as_bytes()),
+            Err(ReadError::Input(CardError::NonContiguousId {
+                line: 2,
+                expected: 2,
+                found: 3
+            }))
+        ));
+    }
+
+    #[test]
+    fn matching_numbers() {
+        let cards = parse_cards(
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 130 7 | 7 130 131",
+        )
+        .unwrap();
+        assert_eq!(vec![17, 48, 83, 86], cards[0].matching_numbers().to_vec());
+        assert_eq!(4, cards[0].win_count());
+        assert_eq!(vec![7, 130], cards[1].matching_numbers().to_vec());
+        assert!(cards[1].scratched_numbers().contains(131));
+        assert!(!cards[1].winning_numbers().contains(131));
+
+        assert_eq!(
+            Err(CardError::Parse(ParseError {
+                line: 1,
+                column: 19,
+                message: "duplicate scratched number 9".to_owned()
+            })),
+            parse_cards("Card 1: 1 2 | 9 3 9")
+        );
+    }
+
+    #[test]
+    fn scoring_rules() {
+        let input = EXAMPLE;
+        let scores = |scoring| get_scores_from_cards(input, &scoring).unwrap();
+
+        assert_eq!(vec![8, 2, 2, 1, 0, 0], scores(Scoring::Doubling));
+        assert_eq!(vec![4, 2, 2, 1, 0, 0], scores(Scoring::Linear));
+        assert_eq!(vec![3, 1, 1, 1, 0, 0], scores(Scoring::Fibonacci));
+        assert_eq!(
+            vec![16, 4, 4, 1, 0, 0],
+            scores(Scoring::Custom(Box::new(|matches| {
+                Some(u128::from(matches * matches))
+            })))
+        );
+
+        let many_matches = (1..=130).map(|n| n.to_string()).join(" ");
+        let input = format!("Card 1: {many_matches} | {many_matches}");
+        assert_eq!(
+            Err(CardError::ScoreOverflow { card: 1 }),
+            get_scores_from_cards(&input, &Scoring::Doubling)
+        );
+        assert_eq!(
+            Ok(vec![130]),
+            get_scores_from_cards(&input, &Scoring::Linear)
+        );
+    }
+
+    #[test]
+    fn copy_rules() {
+        let input = EXAMPLE;
+
+        assert_eq!(
+            Ok(30),
+            count_copies_with::<u64>(input, &CopyRule::default().with_count_only_wrap())
+        );
+        assert_eq!(
+            Ok(116),
+            count_copies_with::<u64>(input, &CopyRule::default().with_copies_per_match(2))
+        );
+
+        let input = "Card 1: 1 | 2
+Card 2: 1 2 | 1 2
+Card 3: 1 2 3 | 1 2 3";
+        assert_eq!(
+            Ok(9),
+            count_copies_with::<u64>(input, &CopyRule::default().with_count_only_wrap())
+        );
+    }
+
     #[test]
     fn part_2_example() {
         let input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
@@ -129,6 +537,71 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
         assert_eq!(30, count_copies_of_cards(input))
     }
 
+    #[test]
+    fn copies_require_contiguous_ids() {
+        let input = "Card 1: 41 48 | 83 48
+Card 3: 13 32 | 61 30";
+        assert_eq!(
+            Err(CardError::NonContiguousId {
+                line: 2,
+                expected: 2,
+                found: 3
+            }),
+            count_copies::<u64>(input)
+        );
+    }
+
+    #[test]
+    fn copies_in_wide_integers() {
+        let winning = (1..=25).map(|n| n.to_string()).join(" ");
+        let input = (1..=40)
+            .map(|id| format!("Card {id}: {winning} | {winning}"))
+            .join("\n");
+
+        assert_eq!(Err(CardError::CountOverflow), count_copies::<u32>(&input));
+        assert_eq!(Ok(1_099_511_414_782), count_copies::<u64>(&input));
+        assert_eq!(Ok(1_099_511_414_782), count_copies::<u128>(&input));
+    }
+
+    #[test]
+    fn cascade_trace() {
+        let cards = parse_cards(
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        )
+        .unwrap();
+        let trace = CascadeTrace::new(&cards).unwrap();
+
+        assert_eq!(
+            CardTrace {
+                id: 4,
+                copies: 8,
+                sources: vec![(1, 1), (2, 2), (3, 4)]
+            },
+            trace.cards()[3]
+        );
+        assert_eq!(
+            "card | copies | won from
+   1 |      1 |
+   2 |      2 | 1 (x1)
+   3 |      4 | 1 (x1), 2 (x2)
+   4 |      8 | 1 (x1), 2 (x2), 3 (x4)
+   5 |     14 | 1 (x1), 3 (x4), 4 (x8)
+   6 |      1 |
+",
+            trace.to_table()
+        );
+        assert!(trace.to_dot().contains("    c3 -> c5 [label=\"4\"];"));
+
+        let frames = trace.frames();
+        assert_eq!(6, frames.len());
+        assert!(frames[1].contains(">    2            2\n+    3            4\n"));
+    }
+
     #[test]
     fn part_2() {
         let input = include_str!("input.txt");
@@ -0,0 +1,66 @@
+use std::collections::BTreeSet;
+
+use itertools::Itertools;
+
+const SMALL_LIMIT: u32 = u128::BITS;
+
+/// A set of card numbers. Numbers below 128, which covers every real card,
+/// are stored as bits of a single `u128`; larger ones fall back to a
+/// `BTreeSet`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NumberSet {
+    small: u128,
+    large: BTreeSet<u32>,
+}
+
+impl NumberSet {
+    pub fn new() -> Self {
+        NumberSet::default()
+    }
+
+    /// Adds `number`, returning whether it was not yet present.
+    pub fn insert(&mut self, number: u32) -> bool {
+        if number < SMALL_LIMIT {
+            let bit = 1u128 << number;
+            let is_new = self.small & bit == 0;
+            self.small |= bit;
+            is_new
+        } else {
+            self.large.insert(number)
+        }
+    }
+
+    pub fn contains(&self, number: u32) -> bool {
+        if number < SMALL_LIMIT {
+            self.small & (1u128 << number) != 0
+        } else {
+            self.large.contains(&number)
+        }
+    }
+
+    pub fn intersection(&self, other: &NumberSet) -> NumberSet {
+        NumberSet {
+            small: self.small & other.small,
+            large: self.large.intersection(&other.large).copied().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.small.count_ones() as usize + self.large.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The numbers in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..SMALL_LIMIT)
+            .filter(|&number| self.small & (1u128 << number) != 0)
+            .chain(self.large.iter().copied())
+    }
+
+    pub fn to_vec(&self) -> Vec<u32> {
+        self.iter().collect_vec()
+    }
+}
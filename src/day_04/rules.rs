@@ -0,0 +1,75 @@
+/// How a card's number of matches is turned into points.
+pub enum Scoring {
+    /// 1 point for the first match, doubled for every further match.
+    Doubling,
+    /// 1 point per match.
+    Linear,
+    /// The `n`th Fibonacci number for `n` matches, starting 1, 1, 2, 3, ...
+    Fibonacci,
+    /// Any function of the match count; `None` signals an overflow.
+    Custom(Box<dyn Fn(u32) -> Option<u128> + Send + Sync>),
+}
+
+impl Scoring {
+    /// The points for `matches` matching numbers, or `None` if they do not
+    /// fit into a `u128`.
+    pub fn score(&self, matches: u32) -> Option<u128> {
+        match self {
+            Scoring::Doubling => match matches {
+                0 => Some(0),
+                _ => 1u128.checked_shl(matches - 1),
+            },
+            Scoring::Linear => Some(u128::from(matches)),
+            Scoring::Fibonacci => {
+                let (mut current, mut next) = (0u128, 1u128);
+                for _ in 0..matches {
+                    (current, next) = (next, current.checked_add(next)?);
+                }
+                Some(current)
+            }
+            Scoring::Custom(score) => score(matches),
+        }
+    }
+}
+
+/// How winning cards award copies of the cards below them.
+///
+/// By default every match awards one copy of one following card and cards
+/// past the end of the table are not awarded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyRule {
+    /// The copies of each following card awarded per held copy of the
+    /// winning card.
+    pub copies_per_match: u32,
+    /// A count-only wrap: awards past the end of the table continue at its
+    /// top, but cards are still processed once, top to bottom, so copies
+    /// wrapped to an already processed card are counted without winning
+    /// further copies. Cascading them would not terminate once two cards
+    /// award each other. A card never awards copies of itself.
+    pub count_only_wrap: bool,
+}
+
+impl Default for CopyRule {
+    fn default() -> Self {
+        CopyRule {
+            copies_per_match: 1,
+            count_only_wrap: false,
+        }
+    }
+}
+
+impl CopyRule {
+    pub fn with_copies_per_match(self, copies_per_match: u32) -> Self {
+        CopyRule {
+            copies_per_match,
+            ..self
+        }
+    }
+
+    pub fn with_count_only_wrap(self) -> Self {
+        CopyRule {
+            count_only_wrap: true,
+            ..self
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::{io, thread, time::Duration};
+
+use itertools::Itertools;
+
+use super::{copies_per_card, Card, CardError, CopyRule};
+
+/// How many copies of a card were held and which cards they were won from.
+/// `sources` pairs the ID of each winning card with the copies it awarded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardTrace {
+    pub id: u32,
+    pub copies: u64,
+    pub sources: Vec<(u32, u64)>,
+}
+
+/// The full cascade of copies awarded while processing a table of cards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CascadeTrace {
+    cards: Vec<CardTrace>,
+    targets: Vec<Vec<usize>>,
+}
+
+impl CascadeTrace {
+    pub fn new(cards: &[Card]) -> Result<Self, CardError> {
+        let copies = copies_per_card::<u64>(cards, &CopyRule::default())?;
+        let mut traces = cards
+            .iter()
+            .zip(copies.iter())
+            .map(|(card, &copies)| CardTrace {
+                id: card.id,
+                copies,
+                sources: vec![],
+            })
+            .collect_vec();
+        let mut targets = vec![];
+
+        for (index, card) in cards.iter().enumerate() {
+            let won = (index + 1..cards.len())
+                .take(card.win_count as usize)
+                .collect_vec();
+            for &target in won.iter() {
+                traces[target].sources.push((card.id, copies[index]));
+            }
+            targets.push(won);
+        }
+
+        Ok(CascadeTrace {
+            cards: traces,
+            targets,
+        })
+    }
+
+    pub fn cards(&self) -> &[CardTrace] {
+        &self.cards
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut table = "card | copies | won from\n".to_owned();
+
+        for card in self.cards.iter() {
+            let sources = card
+                .sources
+                .iter()
+                .map(|(id, copies)| format!("{id} (x{copies})"))
+                .join(", ");
+            table.push_str(format!("{:>4} | {:>6} | {sources}", card.id, card.copies).trim_end());
+            table.push('\n');
+        }
+
+        table
+    }
+
+    /// Renders the cascade as a Graphviz digraph with an edge from every
+    /// winning card to each card it awarded copies of.
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph cascade {\n".to_owned();
+
+        for card in self.cards.iter() {
+            dot.push_str(&format!(
+                "    c{0} [label=\"Card {0}\\n{1} copies\"];\n",
+                card.id, card.copies
+            ));
+        }
+        for card in self.cards.iter() {
+            for (source, copies) in card.sources.iter() {
+                dot.push_str(&format!(
+                    "    c{source} -> c{} [label=\"{copies}\"];\n",
+                    card.id
+                ));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// One frame per processed card, showing the copies known at that point.
+    /// The card being processed is marked with `>` and the cards it awards
+    /// copies to with `+`.
+    pub fn frames(&self) -> Vec<String> {
+        let mut known = vec![1u64; self.cards.len()];
+        let mut frames = vec![];
+
+        for (step, card) in self.cards.iter().enumerate() {
+            for &target in self.targets[step].iter() {
+                known[target] += card.copies;
+            }
+
+            let mut frame = format!("Processing card {}\n", card.id);
+            for (index, other) in self.cards.iter().enumerate() {
+                let marker = if index == step {
+                    '>'
+                } else if self.targets[step].contains(&index) {
+                    '+'
+                } else {
+                    ' '
+                };
+                frame.push_str(&format!("{marker} {:>4} {:>12}\n", other.id, known[index]));
+            }
+            frames.push(frame);
+        }
+
+        frames
+    }
+
+    /// Plays [`CascadeTrace::frames`] on a terminal, clearing the screen
+    /// between frames.
+    pub fn animate(&self, out: &mut impl io::Write, delay: Duration) -> io::Result<()> {
+        for frame in self.frames() {
+            write!(out, "\x1b[2J\x1b[H{frame}")?;
+            out.flush()?;
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+}
@@ -2,6 +2,11 @@ use std::{collections::HashMap, ops::Range};
 
 use itertools::Itertools;
 
+use crate::{
+    parallel,
+    parse::{self, Block, Line, ParseError},
+};
+
 type Seed = u64;
 type Soil = u64;
 type Fertilizer = u64;
@@ -11,8 +16,11 @@ type Temperature = u64;
 type Humidity = u64;
 type Location = u64;
 
+/// The seeds, as single numbers or ranges, and the maps of an almanac.
+type Almanac<S> = (Vec<S>, HashMap<Numbers, Maps>);
+
 #[derive(PartialEq, Eq, Hash, Debug)]
-enum Numbers {
+pub(crate) enum Numbers {
     Seed,
     Soil,
     Fertilizer,
@@ -23,7 +31,7 @@ enum Numbers {
 }
 
 #[derive(Debug)]
-enum Maps {
+pub(crate) enum Maps {
     SeedToSoil(HashMap<Range<Seed>, Range<Soil>>),
     SoilToFertilizer(HashMap<Range<Soil>, Range<Fertilizer>>),
     FertilizerToWater(HashMap<Range<Fertilizer>, Range<Water>>),
@@ -33,37 +41,41 @@ enum Maps {
     HumidityToLocation(HashMap<Range<Humidity>, Range<Location>>),
 }
 
-pub fn get_lowest_location_per_seed(input: &str) -> HashMap<Seed, Location> {
-    let mut result: HashMap<Seed, Location> = HashMap::new();
-    let mut blocks = input.split("\n\n");
-    let seeds = blocks.next().unwrap();
-    let maps = parse_to_maps(blocks.collect_vec());
-    let chain = vec![
-        Numbers::Seed,
-        Numbers::Soil,
-        Numbers::Fertilizer,
-        Numbers::Water,
-        Numbers::Light,
-        Numbers::Temperature,
-        Numbers::Humidity,
-    ];
-
-    for seed in seeds
-        .split(':')
-        .nth(1)
-        .unwrap()
-        .split_whitespace()
-        .map(|s| s.parse::<Seed>().unwrap())
-    {
-        let mut temp = seed;
-        for number in chain.iter() {
-            temp = map_source_to_destination(maps.get(number).unwrap(), temp);
-        }
-
-        result.insert(seed, temp);
-    }
+const CHAIN: [Numbers; 7] = [
+    Numbers::Seed,
+    Numbers::Soil,
+    Numbers::Fertilizer,
+    Numbers::Water,
+    Numbers::Light,
+    Numbers::Temperature,
+    Numbers::Humidity,
+];
+
+pub fn get_lowest_location_per_seed(input: &str) -> Result<HashMap<Seed, Location>, ParseError> {
+    let (seeds, maps) = parse_almanac(input)?;
+
+    let locations = parallel::map(&seeds, |&seed| {
+        CHAIN.iter().fold(seed, |temp, number| {
+            map_source_to_destination(maps.get(number).unwrap(), temp)
+        })
+    });
+
+    Ok(seeds.into_iter().zip(locations).collect())
+}
 
-    result
+/// The lowest location of any seed when the seed numbers are read as pairs
+/// of range start and length, or `None` without seeds.
+pub fn get_lowest_location_of_seed_ranges(input: &str) -> Result<Option<Location>, ParseError> {
+    let (seed_ranges, maps) = parse_seed_ranges(input)?;
+
+    Ok(CHAIN
+        .iter()
+        .fold(seed_ranges, |ranges, number| {
+            map_source_ranges_to_destination(maps.get(number).unwrap(), ranges)
+        })
+        .into_iter()
+        .map(|range| range.start)
+        .min())
 }
 
 fn map_source_to_destination(map: &Maps, source_value: u64) -> u64 {
@@ -91,44 +103,133 @@ fn determine_destination(maps: &HashMap<Range<u64>, Range<u64>>, source_value: u
     }
 }
 
-fn parse_to_maps(input: Vec<&str>) -> HashMap<Numbers, Maps> {
+fn map_source_ranges_to_destination(map: &Maps, ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    match map {
+        Maps::SeedToSoil(maps) => determine_destination_ranges(maps, ranges),
+        Maps::SoilToFertilizer(maps) => determine_destination_ranges(maps, ranges),
+        Maps::FertilizerToWater(maps) => determine_destination_ranges(maps, ranges),
+        Maps::WaterToLight(maps) => determine_destination_ranges(maps, ranges),
+        Maps::LightToTemperature(maps) => determine_destination_ranges(maps, ranges),
+        Maps::TemperatureToHumidity(maps) => determine_destination_ranges(maps, ranges),
+        Maps::HumidityToLocation(maps) => determine_destination_ranges(maps, ranges),
+    }
+}
+
+/// Maps whole ranges at once, splitting them where the source ranges begin
+/// and end. Source ranges are tried in the same order as in
+/// [`determine_destination`], so both agree even if source ranges overlap.
+fn determine_destination_ranges(
+    maps: &HashMap<Range<u64>, Range<u64>>,
+    ranges: Vec<Range<u64>>,
+) -> Vec<Range<u64>> {
+    let mut unmapped = ranges
+        .into_iter()
+        .filter(|range| !range.is_empty())
+        .collect_vec();
+    let mut mapped = vec![];
+
+    for (source, destination) in maps.iter() {
+        let mut remaining = vec![];
+        for range in unmapped {
+            let start = range.start.max(source.start);
+            let end = range.end.min(source.end);
+            if start >= end {
+                remaining.push(range);
+                continue;
+            }
+
+            mapped.push(
+                destination.start + (start - source.start)
+                    ..destination.start + (end - source.start),
+            );
+            if range.start < start {
+                remaining.push(range.start..start);
+            }
+            if end < range.end {
+                remaining.push(end..range.end);
+            }
+        }
+        unmapped = remaining;
+    }
+
+    mapped.extend(unmapped);
+    mapped
+}
+
+/// Like [`parse_almanac`], but reads the seeds as pairs of range start and
+/// length.
+pub(crate) fn parse_seed_ranges(input: &str) -> Result<Almanac<Range<Seed>>, ParseError> {
+    let (seeds, maps) = parse_almanac(input)?;
+    let error = |message: &str| ParseError {
+        line: 1,
+        column: 1,
+        message: message.to_owned(),
+    };
+    if seeds.len() % 2 == 1 {
+        return Err(error("expected pairs of seed range start and length"));
+    }
+
+    let seed_ranges = seeds
+        .into_iter()
+        .tuples()
+        .map(|(start, length)| {
+            let end = start
+                .checked_add(length)
+                .ok_or_else(|| error("seed range end overflows"))?;
+            Ok(start..end)
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    Ok((seed_ranges, maps))
+}
+
+pub(crate) fn parse_almanac(input: &str) -> Result<Almanac<Seed>, ParseError> {
+    let blocks = parse::blocks(input);
+    let (seeds, map_blocks) = blocks.split_first().ok_or(ParseError {
+        line: 1,
+        column: 1,
+        message: "expected 'seeds:'".to_owned(),
+    })?;
+    let seeds_line = seeds.lines()[0];
+    let seeds = seeds_line.integers(seeds_line.after_label("seeds")?)?;
+    let maps = parse_to_maps(map_blocks)?;
+
+    if let Some(missing) = CHAIN.iter().find(|number| !maps.contains_key(number)) {
+        return Err(ParseError {
+            line: input.lines().count(),
+            column: 1,
+            message: format!("missing the map for {missing:?}"),
+        });
+    }
+
+    Ok((seeds, maps))
+}
+
+pub(crate) fn parse_to_maps(blocks: &[Block]) -> Result<HashMap<Numbers, Maps>, ParseError> {
     let mut maps: HashMap<Numbers, Maps> = HashMap::with_capacity(7);
-    for &block in input.iter() {
-        let mut lines = block.lines();
-        let (number, map) = match lines.next().unwrap() {
-            "seed-to-soil map:" => (
-                Numbers::Seed,
-                Maps::SeedToSoil(build_hash_maps(lines.map(parse_line).collect_vec())),
-            ),
-            "soil-to-fertilizer map:" => (
-                Numbers::Soil,
-                Maps::SoilToFertilizer(build_hash_maps(lines.map(parse_line).collect_vec())),
-            ),
-            "fertilizer-to-water map:" => (
-                Numbers::Fertilizer,
-                Maps::FertilizerToWater(build_hash_maps(lines.map(parse_line).collect_vec())),
-            ),
-            "water-to-light map:" => (
-                Numbers::Water,
-                Maps::WaterToLight(build_hash_maps(lines.map(parse_line).collect_vec())),
-            ),
-            "light-to-temperature map:" => (
-                Numbers::Light,
-                Maps::LightToTemperature(build_hash_maps(lines.map(parse_line).collect_vec())),
-            ),
-            "temperature-to-humidity map:" => (
-                Numbers::Temperature,
-                Maps::TemperatureToHumidity(build_hash_maps(lines.map(parse_line).collect_vec())),
-            ),
-            "humidity-to-location map:" => (
-                Numbers::Humidity,
-                Maps::HumidityToLocation(build_hash_maps(lines.map(parse_line).collect_vec())),
-            ),
-            _ => panic!("Unkown Map Type"),
+    for block in blocks.iter() {
+        let (header, lines) = block.lines().split_first().unwrap();
+        let ranges = build_hash_maps(
+            lines
+                .iter()
+                .map(|&line| parse_line(line))
+                .collect::<Result<Vec<_>, ParseError>>()?,
+        );
+        let (number, map) = match header.text().trim_end() {
+            "seed-to-soil map:" => (Numbers::Seed, Maps::SeedToSoil(ranges)),
+            "soil-to-fertilizer map:" => (Numbers::Soil, Maps::SoilToFertilizer(ranges)),
+            "fertilizer-to-water map:" => (Numbers::Fertilizer, Maps::FertilizerToWater(ranges)),
+            "water-to-light map:" => (Numbers::Water, Maps::WaterToLight(ranges)),
+            "light-to-temperature map:" => (Numbers::Light, Maps::LightToTemperature(ranges)),
+            "temperature-to-humidity map:" => {
+                (Numbers::Temperature, Maps::TemperatureToHumidity(ranges))
+            }
+            "humidity-to-location map:" => (Numbers::Humidity, Maps::HumidityToLocation(ranges)),
+            _ => return Err(header.error(0, "unknown map type")),
         };
         maps.insert(number, map);
     }
-    maps
+    Ok(maps)
 }
 
 fn build_hash_maps(maps: Vec<(Range<u64>, Range<u64>)>) -> HashMap<Range<u64>, Range<u64>> {
@@ -139,21 +240,88 @@ fn build_hash_maps(maps: Vec<(Range<u64>, Range<u64>)>) -> HashMap<Range<u64>, R
     result
 }
 
-fn parse_line(line: &str) -> (Range<u64>, Range<u64>) {
-    let numbers: (u64, u64, u64) = line
-        .split_whitespace()
-        .map(|number| number.parse::<u64>().unwrap())
+pub(crate) fn parse_line(line: Line) -> Result<(Range<u64>, Range<u64>), ParseError> {
+    let (destination, source, length): (u64, u64, u64) = line
+        .integers(line.span())?
+        .into_iter()
         .collect_tuple()
-        .unwrap();
-    (
-        numbers.0..(numbers.0 + numbers.2),
-        numbers.1..(numbers.1 + numbers.2),
-    )
+        .ok_or_else(|| line.error(0, "expected three numbers"))?;
+    let end = |start: u64| {
+        start
+            .checked_add(length)
+            .ok_or_else(|| line.error(0, "range end overflows"))
+    };
+
+    Ok((destination..end(destination)?, source..end(source)?))
 }
 
+/// The example from the puzzle description.
+pub(crate) const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, Rng};
+    use proptest::prelude::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn seed_ranges_match_single_seeds(
+            seed in any::<u64>(),
+            seed_ranges in 0..6usize,
+            ranges in 0..5usize,
+        ) {
+            let input = generate::almanac_within(&mut Rng::new(seed), seed_ranges * 2, ranges, 64);
+            let (seeds, maps) = parse_almanac(&input).unwrap();
+            let one_by_one = seeds
+                .into_iter()
+                .tuples()
+                .flat_map(|(start, length)| start..start + length)
+                .map(|seed| {
+                    CHAIN.iter().fold(seed, |value, number| {
+                        map_source_to_destination(maps.get(number).unwrap(), value)
+                    })
+                })
+                .min();
+
+            prop_assert_eq!(one_by_one, get_lowest_location_of_seed_ranges(&input).unwrap(), "{}", input);
+        }
+    }
 
     #[test]
     fn part_1_example() {
@@ -192,7 +360,11 @@ humidity-to-location map:
 56 93 4";
         assert_eq!(
             35,
-            *get_lowest_location_per_seed(input).values().min().unwrap()
+            *get_lowest_location_per_seed(input)
+                .unwrap()
+                .values()
+                .min()
+                .unwrap()
         );
     }
 
@@ -201,54 +373,114 @@ humidity-to-location map:
         let input = include_str!("input.txt");
         println!(
             "The lowest location is {}",
-            *get_lowest_location_per_seed(input).values().min().unwrap()
+            *get_lowest_location_per_seed(input)
+                .unwrap()
+                .values()
+                .min()
+                .unwrap()
         );
     }
 
-    /* #[test]
-            fn part_2_example() {
-                let input = "seeds: 79 14 55 13
-
-    seed-to-soil map:
-    50 98 2
-    52 50 48
-
-    soil-to-fertilizer map:
-    0 15 37
-    37 52 2
-    39 0 15
-
-    fertilizer-to-water map:
-    49 53 8
-    0 11 42
-    42 0 7
-    57 7 4
-
-    water-to-light map:
-    88 18 7
-    18 25 70
-
-    light-to-temperature map:
-    45 77 23
-    81 45 19
-    68 64 13
-
-    temperature-to-humidity map:
-    0 69 1
-    1 0 69
-
-    humidity-to-location map:
-    60 56 37
-    56 93 4";
-                assert_eq!(30, count_copies_of_cards(input))
-            } */
-
-    /*     #[test]
+    #[test]
+    fn almanac_errors() {
+        assert_eq!(
+            ParseError {
+                line: 3,
+                column: 1,
+                message: "unknown map type".to_owned()
+            },
+            parse_almanac("seeds: 1 2\n\nseed-to-dirt map:\n1 2 3")
+                .err()
+                .unwrap()
+        );
+        assert_eq!(
+            ParseError {
+                line: 4,
+                column: 5,
+                message: "invalid number 'x'".to_owned()
+            },
+            parse_almanac("seeds: 1 2\n\nseed-to-soil map:\n1 2 x")
+                .err()
+                .unwrap()
+        );
+        assert_eq!(
+            "missing the map for Soil",
+            parse_almanac("seeds: 1 2\n\nseed-to-soil map:\n1 2 3")
+                .err()
+                .unwrap()
+                .message
+        );
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+        assert_eq!(Ok(Some(46)), get_lowest_location_of_seed_ranges(input));
+    }
+
+    #[test]
     fn part_2() {
         let input = include_str!("input.txt");
         println!(
-            "The sum of all scratch cards is {}",
-            count_copies_of_cards(input)
+            "The lowest location of any seed range is {}",
+            get_lowest_location_of_seed_ranges(input).unwrap().unwrap()
+        );
+    }
+
+    #[test]
+    fn seed_range_errors() {
+        let maps = "seed-to-soil map:\n\nsoil-to-fertilizer map:\n\nfertilizer-to-water map:
+\nwater-to-light map:\n\nlight-to-temperature map:\n\ntemperature-to-humidity map:
+\nhumidity-to-location map:";
+        assert_eq!(
+            Ok(Some(3)),
+            get_lowest_location_of_seed_ranges(&format!("seeds: 7 0 3 2\n\n{maps}"))
+        );
+        assert_eq!(
+            "expected pairs of seed range start and length",
+            parse_seed_ranges(&format!("seeds: 1 2 3\n\n{maps}"))
+                .err()
+                .unwrap()
+                .message
         );
-    } */
+        assert_eq!(
+            "seed range end overflows",
+            parse_seed_ranges(&format!("seeds: 1 {}\n\n{maps}", u64::MAX))
+                .err()
+                .unwrap()
+                .message
+        );
+    }
 }
@@ -1,6 +1,8 @@
 use itertools::Itertools;
 
-struct Race {
+use crate::parse::{self, Line, ParseError, Span};
+
+pub(crate) struct Race {
     time: u64,
     distance_to_beat: u64,
 }
@@ -15,68 +17,141 @@ impl From<(u64, u64)> for Race {
 }
 
 impl Race {
+    /// Holding the button for `h` of `T` milliseconds travels `h * (T - h)`,
+    /// which beats the distance `D` for the `h` strictly between the roots of
+    /// `h² - T·h + D`. The integer square root is corrected by stepping, so
+    /// no floating point rounding is involved.
     fn get_number_of_ways_to_win(&self) -> usize {
-        let mut possibilities = Vec::new();
-        for i in 0..self.time {
-            possibilities.push((i, self.time - i));
+        let time = u128::from(self.time);
+        let distance = u128::from(self.distance_to_beat);
+        let wins = |hold: u128| hold * (time - hold) > distance;
+
+        let Some(discriminant) = (time * time).checked_sub(4 * distance) else {
+            return 0;
+        };
+        let mut shortest = (time - discriminant.isqrt().min(time)) / 2;
+        while shortest > 0 && wins(shortest - 1) {
+            shortest -= 1;
+        }
+        while shortest <= time / 2 && !wins(shortest) {
+            shortest += 1;
         }
 
-        possibilities
-            .iter()
-            .map(|(velocity, remaining_time)| velocity * remaining_time)
-            .filter(|&distance_traveled| distance_traveled > self.distance_to_beat)
-            .count()
+        if shortest > time / 2 {
+            0
+        } else {
+            (time - 2 * shortest + 1) as usize
+        }
     }
 }
 
-pub fn get_number_of_ways_to_win_part1(input: &str) -> Vec<usize> {
-    parse_races_part1(input)
+pub fn get_number_of_ways_to_win_part1(input: &str) -> Result<Vec<usize>, ParseError> {
+    Ok(parse_races_part1(input)?
         .iter()
         .map(Race::get_number_of_ways_to_win)
-        .collect_vec()
+        .collect_vec())
 }
 
-fn parse_races_part1(input: &str) -> Vec<Race> {
-    let lines: (&str, &str) = input.lines().collect_tuple().unwrap();
-    let times = lines
-        .0
-        .split_whitespace()
-        .skip(1)
-        .map(|s| s.parse::<u64>().unwrap());
-    let distances_to_beat = lines
-        .1
-        .split_whitespace()
-        .skip(1)
-        .map(|s| s.parse::<u64>().unwrap());
-    times.zip(distances_to_beat).map(Race::from).collect_vec()
+pub(crate) fn parse_races_part1(input: &str) -> Result<Vec<Race>, ParseError> {
+    let ((time_line, times), (distance_line, distances)) = parse_race_sheet(input)?;
+    let times = time_line.integers::<u64>(times)?;
+    let distances_to_beat = distance_line.integers::<u64>(distances)?;
+    if times.len() != distances_to_beat.len() {
+        return Err(distance_line.error(
+            distances.start(),
+            format!(
+                "expected {} distances but found {}",
+                times.len(),
+                distances_to_beat.len()
+            ),
+        ));
+    }
+
+    Ok(times
+        .into_iter()
+        .zip(distances_to_beat)
+        .map(Race::from)
+        .collect_vec())
 }
 
-pub fn get_number_of_ways_to_win_part2(input: &str) -> usize {
-    parse_races_part2(input).get_number_of_ways_to_win()
+pub fn get_number_of_ways_to_win_part2(input: &str) -> Result<usize, ParseError> {
+    Ok(parse_races_part2(input)?.get_number_of_ways_to_win())
 }
 
-fn parse_races_part2(input: &str) -> Race {
-    let lines: (&str, &str) = input.lines().collect_tuple().unwrap();
-    let time = lines
-        .0
-        .split_whitespace()
-        .skip(1)
-        .collect::<String>()
-        .parse::<u64>()
-        .unwrap();
-    let distance_to_beat = lines
-        .1
-        .split_whitespace()
-        .skip(1)
-        .collect::<String>()
-        .parse::<u64>()
-        .unwrap();
-    Race::from((time, distance_to_beat))
+pub(crate) fn parse_races_part2(input: &str) -> Result<Race, ParseError> {
+    let ((time_line, times), (distance_line, distances)) = parse_race_sheet(input)?;
+    let joined = |line: Line, numbers: Span| {
+        numbers
+            .text()
+            .split_whitespace()
+            .collect::<String>()
+            .parse::<u64>()
+            .map_err(|_| line.error(numbers.start(), "invalid number"))
+    };
+
+    Ok(Race::from((
+        joined(time_line, times)?,
+        joined(distance_line, distances)?,
+    )))
 }
 
+/// A line paired with the text following its label.
+type LabelledLine<'a> = (Line<'a>, Span<'a>);
+
+/// Splits the sheet into its `Time:` and `Distance:` lines.
+fn parse_race_sheet(input: &str) -> Result<(LabelledLine<'_>, LabelledLine<'_>), ParseError> {
+    let (time_line, distance_line) =
+        parse::lines(input)
+            .collect_tuple()
+            .ok_or_else(|| ParseError {
+                line: 1,
+                column: 1,
+                message: "expected a 'Time:' and a 'Distance:' line".to_owned(),
+            })?;
+
+    Ok((
+        (time_line, time_line.after_label("Time")?),
+        (distance_line, distance_line.after_label("Distance")?),
+    ))
+}
+
+/// The example from the puzzle description.
+pub(crate) const EXAMPLE: &str = "Time:      7  15   30
+Distance:  9  40  200
+";
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    /// Tries every button hold time, as the original solution did.
+    fn count_ways_by_trying(race: &Race) -> usize {
+        (0..race.time)
+            .map(|hold| hold * (race.time - hold))
+            .filter(|&distance| distance > race.distance_to_beat)
+            .count()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(5000))]
+
+        #[test]
+        fn closed_form_matches_trying(time in 0..3000u64, distance in 0..2_500_000u64) {
+            let race = Race::from((time, distance));
+            prop_assert_eq!(count_ways_by_trying(&race), race.get_number_of_ways_to_win());
+        }
+    }
+
+    #[test]
+    fn closed_form_edge_cases() {
+        let ways = |time, distance| Race::from((time, distance)).get_number_of_ways_to_win();
+        assert_eq!(0, ways(0, 0));
+        assert_eq!(0, ways(4, 4));
+        assert_eq!(1, ways(4, 3));
+        assert_eq!(3, ways(4, 0));
+        assert_eq!(u64::MAX as usize - 3, ways(u64::MAX, u64::MAX));
+    }
 
     #[test]
     fn part_1_example() {
@@ -85,6 +160,7 @@ Distance:  9  40  200";
         assert_eq!(
             288,
             get_number_of_ways_to_win_part1(input)
+                .unwrap()
                 .iter()
                 .product::<usize>()
         );
@@ -97,16 +173,36 @@ Distance:   207   1394   1209   1014";
         println!(
             "The product of ways to win is {}",
             get_number_of_ways_to_win_part1(input)
+                .unwrap()
                 .iter()
                 .product::<usize>()
         );
     }
 
+    #[test]
+    fn race_sheet_errors() {
+        assert_eq!(
+            "expected 'Distance:'",
+            parse_races_part1("Time: 7\nDistance 9")
+                .err()
+                .unwrap()
+                .message
+        );
+        assert_eq!(
+            ParseError {
+                line: 2,
+                column: 10,
+                message: "expected 2 distances but found 1".to_owned()
+            },
+            parse_races_part1("Time: 7 15\nDistance: 9").err().unwrap()
+        );
+    }
+
     #[test]
     fn part_2_example() {
         let input = "Time:      7  15   30
     Distance:  9  40  200";
-        assert_eq!(71503, get_number_of_ways_to_win_part2(input))
+        assert_eq!(Ok(71503), get_number_of_ways_to_win_part2(input))
     }
 
     #[test]
@@ -115,7 +211,7 @@ Distance:   207   1394   1209   1014";
     Distance:   207   1394   1209   1014";
         println!(
             "The number of ways to win is {}",
-            get_number_of_ways_to_win_part2(input)
+            get_number_of_ways_to_win_part2(input).unwrap()
         );
     }
 }
@@ -0,0 +1,180 @@
+//! Entry points for the fuzz targets in `fuzz/`. Each feeds arbitrary text
+//! to one parser; the targets only check that nothing panics, as every
+//! rejection has to be reported through the parser's error type.
+
+use crate::{
+    day_01, day_02, day_03, day_04, day_05, day_06,
+    parse::{self, ParseError},
+};
+
+/// A parser entry point, named like its fuzz target and corpus directory.
+pub type Target = fn(&str) -> Result<(), ParseError>;
+
+pub const TARGETS: [(&str, Target); 11] = [
+    ("day_01_parse_line", day_01_parse_line),
+    ("day_02_parse_line", day_02_parse_line),
+    ("day_02_parse_sample", day_02_parse_sample),
+    ("day_03_schematic", day_03_schematic),
+    ("day_04_parse_line", day_04_parse_line),
+    ("day_05_parse_line", day_05_parse_line),
+    ("day_05_parse_to_maps", day_05_parse_to_maps),
+    ("day_05_parse_almanac", day_05_parse_almanac),
+    ("day_06_parse_races_part1", day_06_parse_races_part1),
+    ("day_06_parse_races_part2", day_06_parse_races_part2),
+    ("runner", runner),
+];
+
+/// Runs `target` on `data` if it is valid UTF-8, as every parser takes text.
+pub fn run(target: Target, data: &[u8]) {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = target(input);
+    }
+}
+
+pub fn day_01_parse_line(input: &str) -> Result<(), ParseError> {
+    for line in parse::lines(input) {
+        let _ = day_01::parse_line_part1(line);
+        day_01::parse_line_part2(line)?;
+    }
+    Ok(())
+}
+
+pub fn day_02_parse_line(input: &str) -> Result<(), ParseError> {
+    for line in parse::lines(input) {
+        day_02::parse_line(line)?;
+    }
+    Ok(())
+}
+
+pub fn day_02_parse_sample(input: &str) -> Result<(), ParseError> {
+    for line in parse::lines(input) {
+        day_02::parse_sample(line, line.span())?;
+    }
+    Ok(())
+}
+
+/// Schematics are rejected with a grid or a parse error; both are reported
+/// as parse errors here.
+pub fn day_03_schematic(input: &str) -> Result<(), ParseError> {
+    let to_parse_error = |error: day_03::SchematicError| match error {
+        day_03::SchematicError::Parse(error) => error,
+        day_03::SchematicError::Grid(error) => ParseError {
+            line: 1,
+            column: 1,
+            message: error.to_string(),
+        },
+    };
+    let options = day_03::SchematicOptions::default().with_padded_rows();
+    day_03::Schematic::parse_with(input, &options).map_err(to_parse_error)?;
+    day_03::Schematic::parse(input).map_err(to_parse_error)?;
+    Ok(())
+}
+
+pub fn day_04_parse_line(input: &str) -> Result<(), ParseError> {
+    for line in parse::lines(input) {
+        day_04::parse_line(line)?;
+    }
+    Ok(())
+}
+
+pub fn day_05_parse_line(input: &str) -> Result<(), ParseError> {
+    for line in parse::lines(input) {
+        day_05::parse_line(line)?;
+    }
+    Ok(())
+}
+
+pub fn day_05_parse_to_maps(input: &str) -> Result<(), ParseError> {
+    day_05::parse_to_maps(&parse::blocks(input)).map(|_| ())
+}
+
+pub fn day_05_parse_almanac(input: &str) -> Result<(), ParseError> {
+    day_05::parse_almanac(input).map(|_| ())
+}
+
+pub fn day_06_parse_races_part1(input: &str) -> Result<(), ParseError> {
+    day_06::parse_races_part1(input).map(|_| ())
+}
+
+pub fn day_06_parse_races_part2(input: &str) -> Result<(), ParseError> {
+    day_06::parse_races_part2(input).map(|_| ())
+}
+
+/// Solves every puzzle, so the solvers see hostile but well-formed input
+/// too. The runner reports rejected input as an error rather than panicking.
+pub fn runner(input: &str) -> Result<(), ParseError> {
+    for (day, part) in crate::runner::puzzles() {
+        let _ = crate::runner::solve(day, part, input.as_bytes());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::generate::Rng;
+
+    const INTERESTING: [&str; 12] = [
+        "0",
+        "9",
+        " ",
+        ":",
+        ";",
+        ",",
+        "|",
+        "\n",
+        "\r\n",
+        "é",
+        "99999999999999999999",
+        "-",
+    ];
+
+    /// Edits `input` at random, keeping it valid UTF-8.
+    fn mutate(rng: &mut Rng, input: &str) -> String {
+        let mut chars = input.chars().collect::<Vec<_>>();
+        for _ in 0..rng.range(1..=4) {
+            let at = rng.index(chars.len() + 1);
+            match rng.range(0..=3) {
+                0 => chars.truncate(at),
+                1 if at < chars.len() => {
+                    chars.remove(at);
+                }
+                2 if at < chars.len() => {
+                    let end = (at + rng.index(8) + 1).min(chars.len());
+                    let copy = chars[at..end].to_vec();
+                    chars.splice(at..at, copy);
+                }
+                _ => {
+                    let insert = rng.pick::<&str>(&INTERESTING).chars().collect::<Vec<_>>();
+                    chars.splice(at..at, insert);
+                }
+            }
+        }
+        chars.into_iter().collect()
+    }
+
+    /// Runs every target on its seed corpus and on mutations of it, a cheap
+    /// stand-in for `cargo fuzz` that runs on stable.
+    #[test]
+    fn corpus_and_mutations_do_not_panic() {
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus");
+        let mut rng = Rng::new(12023);
+
+        for (name, target) in TARGETS {
+            let entries = fs::read_dir(corpus.join(name))
+                .unwrap_or_else(|error| panic!("no seed corpus for {name}: {error}"));
+            for entry in entries {
+                let data = fs::read(entry.unwrap().path()).unwrap();
+                run(target, &data);
+
+                let input = String::from_utf8_lossy(&data);
+                for _ in 0..200 {
+                    let mutated = mutate(&mut rng, &input);
+                    let _ = target(&mutated);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::{fmt::Write, ops::RangeInclusive};
+
+use itertools::Itertools;
+
+/// A small seeded pseudo-random generator (SplitMix64), so generated inputs
+/// can be reproduced from their seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// A generator for one item of a larger input, advancing this one by a
+    /// single step. Drawing every item from its own fork keeps a smaller input
+    /// a prefix of a larger one with the same seed, so property tests can
+    /// shrink the size of a failing input.
+    pub fn fork(&mut self) -> Rng {
+        Rng::new(self.next_u64())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = range.into_inner();
+        match (end - start).checked_add(1) {
+            Some(len) => start + self.next_u64() % len,
+            None => self.next_u64(),
+        }
+    }
+
+    /// An index into a slice of `len` items, which must not be empty.
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len > 0, "cannot pick an index into an empty slice");
+        self.range(0..=len as u64 - 1) as usize
+    }
+
+    /// `true` with a probability of `percent` in 100.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.range(1..=100) <= percent
+    }
+
+    /// A random item of `items`, which must not be empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+const NUMBER_WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// A day 1 calibration document of `lines` lines. Every line holds at least
+/// one digit among lowercase letters and spelled out numbers.
+pub fn calibration_document(rng: &mut Rng, lines: usize) -> String {
+    let mut document = String::new();
+
+    for _ in 0..lines {
+        let rng = &mut rng.fork();
+        let mut line = String::new();
+        let digit_at = rng.index(6);
+        for index in 0..6 {
+            if index == digit_at || rng.chance(15) {
+                let _ = write!(line, "{}", rng.range(1..=9));
+            } else if rng.chance(40) {
+                line.push_str(rng.pick::<&str>(&NUMBER_WORDS));
+            } else {
+                for _ in 0..rng.range(1..=4) {
+                    line.push(char::from(b'a' + rng.range(0..=25) as u8));
+                }
+            }
+        }
+        document.push_str(&line);
+        document.push('\n');
+    }
+
+    document
+}
+
+/// Day 2 records of `games` games with up to `max_samples` samples each.
+pub fn game_records(rng: &mut Rng, games: usize, max_samples: usize) -> String {
+    let mut records = String::new();
+
+    for id in 1..=games {
+        let rng = &mut rng.fork();
+        let samples = (0..rng.range(1..=max_samples.max(1) as u64))
+            .map(|_| {
+                let mut colours = ["red", "green", "blue"];
+                rng.shuffle(&mut colours);
+                colours[..rng.range(1..=3) as usize]
+                    .iter()
+                    .map(|colour| format!("{} {colour}", rng.range(1..=20)))
+                    .join(", ")
+            })
+            .join("; ");
+        let _ = writeln!(records, "Game {id}: {samples}");
+    }
+
+    records
+}
+
+/// A day 3 engine schematic of `rows` by `columns` cells.
+pub fn schematic(rng: &mut Rng, rows: usize, columns: usize) -> String {
+    const SYMBOLS: [char; 10] = ['*', '#', '+', '$', '/', '@', '=', '%', '-', '&'];
+    let mut schematic = String::new();
+
+    for _ in 0..rows {
+        let rng = &mut rng.fork();
+        let mut col = 0;
+        while col < columns {
+            if rng.chance(12) {
+                let digits = rng.index(3).min(columns - col - 1) + 1;
+                let _ = write!(
+                    schematic,
+                    "{}",
+                    rng.range(10u64.pow(digits as u32 - 1)..=10u64.pow(digits as u32) - 1)
+                );
+                col += digits;
+                if col < columns {
+                    schematic.push('.');
+                    col += 1;
+                }
+            } else {
+                schematic.push(if rng.chance(8) {
+                    *rng.pick(&SYMBOLS)
+                } else {
+                    '.'
+                });
+                col += 1;
+            }
+        }
+        schematic.push('\n');
+    }
+
+    schematic
+}
+
+/// A day 4 table of `cards` scratchcards, each with `winning` winning and
+/// `scratched` scratched numbers between 1 and 99. Like the real tables,
+/// most cards have no matches, which keeps the number of copies in check.
+pub fn scratchcards(rng: &mut Rng, cards: usize, winning: usize, scratched: usize) -> String {
+    let winning = winning.min(99);
+    let scratched = scratched.min(99 - winning);
+    let mut table = String::new();
+    let width = cards.to_string().len();
+    let format = |numbers: &[u64]| {
+        numbers
+            .iter()
+            .map(|number| format!("{number:>2}"))
+            .join(" ")
+    };
+
+    for id in 1..=cards {
+        let rng = &mut rng.fork();
+        let matches = match rng.chance(75) {
+            true => 0,
+            false => rng.range(1..=4) as usize,
+        }
+        .min(winning)
+        .min(scratched);
+
+        let mut numbers = (1..=99).collect_vec();
+        rng.shuffle(&mut numbers);
+        let (winning_numbers, rest) = numbers.split_at(winning);
+        let mut scratched_numbers = winning_numbers[..matches]
+            .iter()
+            .chain(&rest[..scratched - matches])
+            .copied()
+            .collect_vec();
+        rng.shuffle(&mut scratched_numbers);
+
+        let _ = writeln!(
+            table,
+            "Card {id:>width$}: {} | {}",
+            format(winning_numbers),
+            format(&scratched_numbers)
+        );
+    }
+
+    table
+}
+
+/// A day 5 almanac with `seeds` seeds and up to `ranges` non-overlapping
+/// ranges per map.
+pub fn almanac(rng: &mut Rng, seeds: usize, ranges: usize) -> String {
+    almanac_within(rng, seeds, ranges, 1 << 32)
+}
+
+/// Like [`almanac`], but with every number below `limit`, which must be at
+/// least 1. Small limits keep checking seed ranges one seed at a time cheap.
+pub fn almanac_within(rng: &mut Rng, seeds: usize, ranges: usize, limit: u64) -> String {
+    const MAPS: [&str; 7] = [
+        "seed-to-soil",
+        "soil-to-fertilizer",
+        "fertilizer-to-water",
+        "water-to-light",
+        "light-to-temperature",
+        "temperature-to-humidity",
+        "humidity-to-location",
+    ];
+    let seeds_rng = &mut rng.fork();
+    let seeds = (0..seeds).map(|_| seeds_rng.range(0..=limit - 1)).join(" ");
+    let mut almanac = format!("seeds: {seeds}\n");
+
+    // Ranges are laid out left to right with gaps, so they never overlap.
+    let spread = (limit / 16).max(1);
+    for map in MAPS {
+        let rng = &mut rng.fork();
+        let _ = write!(almanac, "\n{map} map:\n");
+        let mut cursor = 0;
+        for _ in 0..ranges {
+            let rng = &mut rng.fork();
+            let start = cursor + rng.range(0..=spread - 1);
+            let length = rng.range(1..=spread);
+            if start + length > limit {
+                break;
+            }
+            let destination = rng.range(0..=limit - length);
+            let _ = writeln!(almanac, "{destination} {start} {length}");
+            cursor = start + length;
+        }
+    }
+
+    almanac
+}
+
+/// A day 6 race sheet of `races` races, each of which can be won.
+pub fn race_sheet(rng: &mut Rng, races: usize) -> String {
+    let races = (0..races)
+        .map(|_| {
+            let rng = &mut rng.fork();
+            let time = rng.range(2..=99);
+            let record = (time / 2) * (time - time / 2);
+            (time, rng.range(0..=record - 1))
+        })
+        .collect_vec();
+    let column = |value: u64| format!(" {value:>4}");
+
+    format!(
+        "Time:    {}\nDistance:{}\n",
+        races.iter().map(|&(time, _)| column(time)).join(""),
+        races.iter().map(|&(_, distance)| column(distance)).join("")
+    )
+}
+
+/// A size giving inputs similar to the real ones. Day 6 stays small, as the
+/// race of part 2 joins the numbers of all races.
+pub fn default_size(day: u32) -> usize {
+    match day {
+        3 => 140,
+        5 => 20,
+        6 => 4,
+        _ => 200,
+    }
+}
+
+/// A random input for `day`, scaled by `size`: the number of lines, games,
+/// cards, rows, seeds or races. Returns `None` for unknown days.
+pub fn generate(day: u32, seed: u64, size: usize) -> Option<String> {
+    let rng = &mut Rng::new(seed);
+    let input = match day {
+        1 => calibration_document(rng, size),
+        2 => game_records(rng, size, 6),
+        3 => schematic(rng, size, size),
+        4 => scratchcards(rng, size, 10, 25),
+        5 => almanac(rng, size, 8),
+        6 => race_sheet(rng, size),
+        _ => return None,
+    };
+
+    Some(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner;
+
+    #[test]
+    #[should_panic(expected = "cannot pick an index into an empty slice")]
+    fn pick_from_nothing() {
+        Rng::new(1).pick::<u32>(&[]);
+    }
+
+    #[test]
+    fn reproducible() {
+        assert_eq!(generate(4, 7, 20), generate(4, 7, 20));
+        assert_ne!(generate(4, 7, 20), generate(4, 8, 20));
+        assert_eq!(None, generate(26, 7, 20));
+        for day in [1, 2, 4] {
+            let small = generate(day, 7, 5).unwrap();
+            assert!(generate(day, 7, 6).unwrap().starts_with(&small));
+        }
+        let small = schematic(&mut Rng::new(7), 5, 10);
+        assert!(schematic(&mut Rng::new(7), 6, 10).starts_with(&small));
+
+        let mut rng = Rng::new(0);
+        assert!((0..1000).all(|_| (3..=5).contains(&rng.range(3..=5))));
+        rng.range(0..=u64::MAX);
+    }
+
+    #[test]
+    fn generated_inputs_are_valid() {
+        for seed in 0..20 {
+            for (day, part) in runner::puzzles() {
+                // The joined race of day 6 part 2 grows tenfold per race.
+                let size = if day == 6 { 3 } else { 30 };
+                let input = generate(day, seed, size).unwrap();
+                assert!(
+                    runner::solve(day, part, input.as_bytes()).is_ok(),
+                    "day {day} part {part}, seed {seed}:\n{input}"
+                );
+            }
+        }
+
+        let sheet = race_sheet(&mut Rng::new(3), 4);
+        assert!(crate::day_06::get_number_of_ways_to_win_part1(&sheet)
+            .unwrap()
+            .iter()
+            .all(|&ways| ways > 0));
+    }
+}
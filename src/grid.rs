@@ -0,0 +1,305 @@
+use std::{error::Error, fmt};
+
+use array2d::Array2D;
+use itertools::Itertools;
+
+use crate::parse;
+
+/// A `(row, column)` index into a [`Grid`].
+pub type Position = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::NorthEast => (-1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (1, 0),
+            Direction::SouthWest => (1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    /// A row (1-based `line`) has a different length than the first row.
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line} has {found} cells but {expected} were expected"
+            ),
+        }
+    }
+}
+
+impl Error for GridError {}
+
+/// A rectangular grid of cells, indexed by [`Position`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Array2D<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn from_rows(rows: &[Vec<T>]) -> Result<Self, GridError> {
+        let expected = rows.first().map_or(0, Vec::len);
+        if let Some((index, row)) = rows.iter().find_position(|row| row.len() != expected) {
+            return Err(GridError::RaggedRow {
+                line: index + 1,
+                expected,
+                found: row.len(),
+            });
+        }
+
+        Ok(Grid {
+            cells: Array2D::from_rows(rows).expect("rows have equal length"),
+        })
+    }
+
+    /// Like [`Grid::from_rows`], but extends rows shorter than the longest
+    /// one with `fill` instead of rejecting them.
+    pub fn from_rows_padded(rows: &[Vec<T>], fill: T) -> Self {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let rows = rows
+            .iter()
+            .map(|row| {
+                let mut row = row.clone();
+                row.resize(width, fill.clone());
+                row
+            })
+            .collect_vec();
+
+        Grid::from_rows(&rows).expect("rows are padded to equal length")
+    }
+}
+
+impl Grid<char> {
+    /// Builds a grid with one row per line and one cell per character.
+    /// Lines may end in `\n` or `\r\n`.
+    pub fn parse(input: &str) -> Result<Self, GridError> {
+        Grid::from_rows(&char_rows(input))
+    }
+
+    /// Like [`Grid::parse`], but pads short lines with `fill`.
+    pub fn parse_padded(input: &str, fill: char) -> Self {
+        Grid::from_rows_padded(&char_rows(input), fill)
+    }
+}
+
+fn char_rows(input: &str) -> Vec<Vec<char>> {
+    parse::lines(input)
+        .map(|line| line.text().chars().collect_vec())
+        .collect_vec()
+}
+
+impl<T> Grid<T> {
+    pub fn num_rows(&self) -> usize {
+        self.cells.num_rows()
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.cells.num_columns()
+    }
+
+    pub fn get(&self, (row, col): Position) -> Option<&T> {
+        self.cells.get(row, col)
+    }
+
+    /// Returns the position one step from `position` in `direction`, if it
+    /// lies within the grid.
+    pub fn step(&self, (row, col): Position, direction: Direction) -> Option<Position> {
+        let (row_offset, col_offset) = direction.offset();
+        let row = row.checked_add_signed(row_offset)?;
+        let col = col.checked_add_signed(col_offset)?;
+
+        (row < self.num_rows() && col < self.num_columns()).then_some((row, col))
+    }
+
+    /// The orthogonally adjacent positions of `position` within the grid.
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        Direction::ORTHOGONAL
+            .into_iter()
+            .filter_map(move |direction| self.step(position, direction))
+    }
+
+    /// The orthogonally and diagonally adjacent positions of `position`
+    /// within the grid.
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(position, direction))
+    }
+
+    /// Walks from `position` in `direction` until the edge of the grid,
+    /// excluding `position` itself.
+    pub fn walk(
+        &self,
+        position: Position,
+        direction: Direction,
+    ) -> impl Iterator<Item = (Position, &T)> + '_ {
+        std::iter::successors(self.step(position, direction), move |&position| {
+            self.step(position, direction)
+        })
+        .map(|position| (position, &self.cells[position]))
+    }
+
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (Position, &T)> + '_ {
+        let num_columns = if row < self.num_rows() {
+            self.num_columns()
+        } else {
+            0
+        };
+        (0..num_columns).map(move |col| ((row, col), &self.cells[(row, col)]))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = (Position, &T)> + '_ {
+        let num_rows = if col < self.num_columns() {
+            self.num_rows()
+        } else {
+            0
+        };
+        (0..num_rows).map(move |row| ((row, col), &self.cells[(row, col)]))
+    }
+
+    /// Iterates over all cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> + '_ {
+        self.cells.enumerate_row_major()
+    }
+
+    pub fn find<F>(&self, predicate: F) -> Option<Position>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(position, _)| position)
+    }
+
+    pub fn find_all<F>(&self, predicate: F) -> Vec<Position>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.iter()
+            .filter(|(_, cell)| predicate(cell))
+            .map(|(position, _)| position)
+            .collect_vec()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.rows_iter() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let input = "ab\ncd\nef\n";
+        let grid = Grid::parse(input).unwrap();
+        assert_eq!((3, 2), (grid.num_rows(), grid.num_columns()));
+        assert_eq!(Some(&'d'), grid.get((1, 1)));
+        assert_eq!(None, grid.get((1, 2)));
+        assert_eq!(input, grid.to_string());
+    }
+
+    #[test]
+    fn ragged_rows() {
+        assert_eq!(
+            GridError::RaggedRow {
+                line: 3,
+                expected: 2,
+                found: 1
+            },
+            Grid::parse("ab\ncd\ne").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn padded_rows() {
+        let grid = Grid::parse_padded("abc\r\nd\r\nef", '.');
+        assert_eq!("abc\nd..\nef.\n", grid.to_string());
+    }
+
+    #[test]
+    fn neighbours_are_bounds_checked() {
+        let grid = Grid::parse("abc\ndef\nghi").unwrap();
+        assert_eq!(vec![(0, 1), (1, 0)], grid.neighbours4((0, 0)).collect_vec());
+        assert_eq!(
+            vec![(0, 1), (1, 1), (1, 0)],
+            grid.neighbours8((0, 0)).collect_vec()
+        );
+        assert_eq!(8, grid.neighbours8((1, 1)).count());
+        assert_eq!(3, grid.neighbours8((2, 2)).count());
+    }
+
+    #[test]
+    fn walks() {
+        let grid = Grid::parse("abc\ndef\nghi").unwrap();
+        let cells = |iter: &mut dyn Iterator<Item = (Position, &char)>| {
+            iter.map(|(_, &c)| c).collect::<String>()
+        };
+        assert_eq!("bc", cells(&mut grid.walk((0, 0), Direction::East)));
+        assert_eq!("ea", cells(&mut grid.walk((2, 2), Direction::NorthWest)));
+        assert_eq!("", cells(&mut grid.walk((0, 1), Direction::North)));
+        assert_eq!("def", cells(&mut grid.row(1)));
+        assert_eq!("cfi", cells(&mut grid.column(2)));
+        assert_eq!(Some((1, 2)), grid.find(|&c| c == 'f'));
+        assert_eq!(vec![(0, 0), (1, 1)], grid.find_all(|&c| "ae".contains(c)));
+    }
+}
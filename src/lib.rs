@@ -1,6 +1,15 @@
+pub mod calendar;
 pub mod day_01;
 pub mod day_02;
 pub mod day_03;
 pub mod day_04;
 pub mod day_05;
 pub mod day_06;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
+pub mod generate;
+pub mod grid;
+pub mod parallel;
+pub mod parse;
+pub mod runner;
+pub mod site;
@@ -0,0 +1,472 @@
+use std::{
+    env, fs,
+    io::{self, BufReader},
+    path::Path,
+    process::{Command, ExitCode},
+    thread,
+    time::Duration,
+};
+
+use aoc_12023::{calendar, day_02, day_03, day_04, generate, grid::Grid, runner, site};
+
+const INPUTS_DIR: &str = "inputs";
+
+/// How often `watch` looks for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Carries the timings of the last run over to a rebuilt `watch`.
+const WATCH_TIMINGS_VAR: &str = "AOC_WATCH_TIMINGS";
+
+const USAGE: &str = "Usage:
+    aoc run <day> <part> [input|-]
+    aoc results <plain|json|csv> [day]
+    aoc fetch <day>
+    aoc submit <day> <part> [input]
+    aoc leaderboard <file|id> [day]
+    aoc watch <day> [--build]
+    aoc countdown
+    aoc generate <day> [seed] [size]
+    aoc filter-games <expression> [input]
+    aoc export-games <json|csv> [input]
+    aoc schematic-graph <dot|graphml> [input]
+    aoc render-schematic <ansi|html|svg> [input]
+    aoc trace-cards <table|dot|animate> [input]";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+
+    let result = match args.iter().map(String::as_str).collect::<Vec<&str>>()[..] {
+        ["run", day, part] => run(day, part, None),
+        ["run", day, part, input] => run(day, part, Some(input)),
+        ["results", format] => results(format, None),
+        ["results", format, day] => results(format, Some(day)),
+        ["fetch", day] => fetch(day),
+        ["submit", day, part] => submit(day, part, None),
+        ["submit", day, part, input] => submit(day, part, Some(input)),
+        ["leaderboard", source] => leaderboard(source, None),
+        ["leaderboard", source, day] => leaderboard(source, Some(day)),
+        ["watch", day] => watch(day, false),
+        ["watch", day, "--build"] => watch(day, true),
+        ["countdown"] => countdown(),
+        ["generate", day] => generate(day, None, None),
+        ["generate", day, seed] => generate(day, Some(seed), None),
+        ["generate", day, seed, size] => generate(day, Some(seed), Some(size)),
+        ["filter-games", expression] => filter_games(expression, &default_input(2)),
+        ["filter-games", expression, input] => filter_games(expression, input),
+        ["export-games", format] => export_games(format, &default_input(2)),
+        ["export-games", format, input] => export_games(format, input),
+        ["schematic-graph", format] => schematic_graph(format, &default_input(3)),
+        ["schematic-graph", format, input] => schematic_graph(format, input),
+        ["render-schematic", format] => render_schematic(format, &default_input(3)),
+        ["render-schematic", format, input] => render_schematic(format, input),
+        ["trace-cards", format] => trace_cards(format, &default_input(4)),
+        ["trace-cards", format, input] => trace_cards(format, input),
+        _ => Err(USAGE.to_owned()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Solves a puzzle, reading the input from `input_path`, from standard input
+/// for `-`, or from the day's default input file.
+fn run(day: &str, part: &str, input_path: Option<&str>) -> Result<(), String> {
+    let day = day
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid day '{day}'"))?;
+    let part = part
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid part '{part}'"))?;
+    check_unlocked(day)?;
+
+    let answer = match input_path {
+        Some("-") => runner::solve(day, part, io::stdin().lock()).map_err(|e| e.to_string()),
+        input_path => {
+            let input_path = input_path
+                .map(str::to_owned)
+                .unwrap_or_else(|| default_input(day));
+            let file = fs::File::open(&input_path)
+                .map_err(|e| format!("Cannot read {input_path}: {e}"))?;
+            runner::solve(day, part, BufReader::new(file)).map_err(|e| format!("{input_path}: {e}"))
+        }
+    }?;
+
+    println!("{answer}");
+
+    Ok(())
+}
+
+/// Solves every puzzle, or those of one day, on the default inputs.
+fn results(format: &str, day: Option<&str>) -> Result<(), String> {
+    let format = match format {
+        "plain" => runner::OutputFormat::Plain,
+        "json" => runner::OutputFormat::Json,
+        "csv" => runner::OutputFormat::Csv,
+        _ => return Err(format!("Unknown output format '{format}'")),
+    };
+    let day = day
+        .map(|day| {
+            day.parse::<u32>()
+                .map_err(|_| format!("Invalid day '{day}'"))
+        })
+        .transpose()?;
+
+    let mut solutions = vec![];
+    for (puzzle_day, part) in runner::available_puzzles(calendar::now()) {
+        if day.is_some_and(|day| day != puzzle_day) {
+            continue;
+        }
+        let input_path = default_input(puzzle_day);
+        let file =
+            fs::File::open(&input_path).map_err(|e| format!("Cannot read {input_path}: {e}"))?;
+        let solution = runner::run(puzzle_day, part, BufReader::new(file))
+            .map_err(|e| format!("{input_path}: {e}"))?;
+        solutions.push(solution);
+    }
+
+    print!("{}", runner::format_solutions(&solutions, format));
+
+    Ok(())
+}
+
+/// Downloads the input of `day` into the input cache, unless it is cached.
+fn fetch(day: &str) -> Result<(), String> {
+    let day = day
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid day '{day}'"))?;
+    check_unlocked(day)?;
+    let cache = site::InputCache::new(INPUTS_DIR);
+
+    match site::fetch_input(&site::Client::from_env(), &cache, day).map_err(|e| e.to_string())? {
+        site::Fetched::Cached(path) => println!("Already cached at {}", path.display()),
+        site::Fetched::Downloaded(path) => println!("Downloaded to {}", path.display()),
+    }
+
+    Ok(())
+}
+
+/// Solves a puzzle and submits the answer, unless the answers store knows
+/// the answer is wrong.
+fn submit(day: &str, part: &str, input_path: Option<&str>) -> Result<(), String> {
+    let day = day
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid day '{day}'"))?;
+    let part = part
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid part '{part}'"))?;
+    check_unlocked(day)?;
+    let input_path = input_path.map_or_else(|| default_input(day), str::to_owned);
+
+    let file = fs::File::open(&input_path).map_err(|e| format!("Cannot read {input_path}: {e}"))?;
+    let answer =
+        runner::solve(day, part, BufReader::new(file)).map_err(|e| format!("{input_path}: {e}"))?;
+    println!("Day {day} part {part}: {answer}");
+
+    let mut store = site::AnswerStore::open(Path::new(INPUTS_DIR).join("answers.tsv"))
+        .map_err(|e| e.to_string())?;
+    let outcome = site::submit_answer(&site::Client::from_env(), &mut store, day, part, &answer)
+        .map_err(|e| e.to_string())?;
+    println!("The answer is {outcome}");
+
+    Ok(())
+}
+
+/// Shows the standings of a private leaderboard, or everyone's times on one
+/// day. `source` is either an exported JSON file or the leaderboard's ID.
+fn leaderboard(source: &str, day: Option<&str>) -> Result<(), String> {
+    let leaderboard = if Path::new(source).is_file() {
+        let json = fs::read_to_string(source).map_err(|e| format!("Cannot read {source}: {e}"))?;
+        site::Leaderboard::parse(&json).map_err(|e| format!("{source}: {e}"))?
+    } else {
+        let id = source
+            .parse::<u64>()
+            .map_err(|_| format!("No such file or leaderboard ID '{source}'"))?;
+        site::fetch_leaderboard(&site::Client::from_env(), id).map_err(|e| e.to_string())?
+    };
+
+    match day {
+        None => print!("{}", site::standings_table(&leaderboard)),
+        Some(day) => {
+            let day = day
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid day '{day}'"))?;
+            print!(
+                "{}",
+                site::day_table(&leaderboard, day).map_err(|e| e.to_string())?
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Solves the examples and the input of `day` whenever the input changes, or
+/// with `build` whenever anything does, rebuilding and restarting first.
+fn watch(day: &str, build: bool) -> Result<(), String> {
+    let day = day
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid day '{day}'"))?;
+    check_unlocked(day)?;
+    let input_path = default_input(day);
+    let exe = env::current_exe().map_err(|e| format!("Cannot find the aoc binary: {e}"))?;
+
+    let mut watcher = if build {
+        runner::Watcher::new([input_path.as_str(), "src", "Cargo.toml"])
+    } else {
+        runner::Watcher::new([input_path.as_str()])
+    };
+    let mut previous = env::var(WATCH_TIMINGS_VAR)
+        .map(|timings| runner::decode_timings(&timings))
+        .unwrap_or_default();
+
+    loop {
+        let checked = runner::check(day, &watch_cases(day, &input_path));
+        print!("{}", runner::format_report(day, &checked, &previous));
+        previous.extend(runner::timings(&checked));
+
+        // Waits for a change, and with `build` for one that also builds.
+        loop {
+            while !watcher.changed() {
+                thread::sleep(POLL_INTERVAL);
+            }
+            if !build {
+                break;
+            }
+
+            let built = fs::metadata(&exe)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            let mut cargo = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned()));
+            cargo.args(["build", "--bin", "aoc"]);
+            if !cfg!(debug_assertions) {
+                cargo.arg("--release");
+            }
+            match cargo.status() {
+                Ok(status) if status.success() => {}
+                Ok(_) => {
+                    eprintln!("Build failed, waiting for changes");
+                    continue;
+                }
+                Err(e) => return Err(format!("Cannot run cargo: {e}")),
+            }
+            if fs::metadata(&exe)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                != built
+            {
+                return Err(restart(&exe, &previous));
+            }
+            break;
+        }
+    }
+}
+
+/// The examples of `day` and, if it can be read, its input, which is checked
+/// against the correct answers in the answers store.
+fn watch_cases(day: u32, input_path: &str) -> Vec<runner::Case> {
+    let mut cases = runner::examples(day)
+        .map(|example| runner::Case {
+            name: "example".to_owned(),
+            part: example.part,
+            input: example.input.to_owned(),
+            expected: Some(example.answer.to_owned()),
+        })
+        .collect::<Vec<_>>();
+
+    let store = site::AnswerStore::open(Path::new(INPUTS_DIR).join("answers.tsv")).ok();
+    match fs::read_to_string(input_path) {
+        Ok(input) => cases.extend(
+            runner::puzzles()
+                .into_iter()
+                .filter(|&(puzzle_day, _)| puzzle_day == day)
+                .map(|(_, part)| runner::Case {
+                    name: "input".to_owned(),
+                    part,
+                    input: input.clone(),
+                    expected: store
+                        .as_ref()
+                        .and_then(|store| store.correct_answer(day, part))
+                        .map(str::to_owned),
+                }),
+        ),
+        Err(e) => eprintln!("Cannot read {input_path}: {e}"),
+    }
+
+    cases
+}
+
+/// Replaces this process with the rebuilt binary, returning why it could
+/// not.
+#[cfg(unix)]
+fn restart(exe: &Path, timings: &runner::Timings) -> String {
+    use std::os::unix::process::CommandExt;
+
+    let error = Command::new(exe)
+        .args(env::args_os().skip(1))
+        .env(WATCH_TIMINGS_VAR, runner::encode_timings(timings))
+        .exec();
+    format!("Cannot restart {}: {error}", exe.display())
+}
+
+#[cfg(not(unix))]
+fn restart(_exe: &Path, _timings: &runner::Timings) -> String {
+    "Rebuilt, restart watch to run the new build".to_owned()
+}
+
+/// Shows how long until the next puzzle unlocks.
+fn countdown() -> Result<(), String> {
+    let now = calendar::now();
+    let today = calendar::puzzle_date(now);
+
+    match calendar::next_unlock(now) {
+        Some((day, wait)) => println!(
+            "It is {today}; day {day} of {} unlocks in {}",
+            calendar::event_label(),
+            calendar::format_duration(wait)
+        ),
+        None => println!(
+            "It is {today}; every day of {} is unlocked",
+            calendar::event_label()
+        ),
+    }
+
+    Ok(())
+}
+
+fn generate(day: &str, seed: Option<&str>, size: Option<&str>) -> Result<(), String> {
+    let day = day
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid day '{day}'"))?;
+    let seed = match seed {
+        Some(seed) => seed
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid seed '{seed}'"))?,
+        None => 12023,
+    };
+    let size = match size {
+        Some(size) => size
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid size '{size}'"))?,
+        None => generate::default_size(day),
+    };
+
+    let input =
+        generate::generate(day, seed, size).ok_or_else(|| format!("No generator for day {day}"))?;
+    print!("{input}");
+
+    Ok(())
+}
+
+fn filter_games(expression: &str, input_path: &str) -> Result<(), String> {
+    let filter = day_02::Filter::parse(expression).map_err(|e| e.to_string())?;
+    let games = read_games(input_path)?;
+
+    for game in games.iter().filter(|game| filter.matches(game)) {
+        println!("{}", game.id());
+    }
+
+    Ok(())
+}
+
+fn export_games(format: &str, input_path: &str) -> Result<(), String> {
+    let games = read_games(input_path)?;
+
+    match format {
+        "json" => println!("{}", day_02::games_to_json(&games)),
+        "csv" => print!("{}", day_02::games_to_csv(&games)),
+        _ => return Err(format!("Unknown export format '{format}'")),
+    }
+
+    Ok(())
+}
+
+fn schematic_graph(format: &str, input_path: &str) -> Result<(), String> {
+    let input = read_input(input_path)?;
+    let schematic = day_03::Schematic::parse(&input).map_err(|e| format!("{input_path}: {e}"))?;
+
+    match format {
+        "dot" => print!("{}", day_03::schematic_to_dot(&schematic)),
+        "graphml" => print!("{}", day_03::schematic_to_graphml(&schematic)),
+        _ => return Err(format!("Unknown graph format '{format}'")),
+    }
+
+    Ok(())
+}
+
+fn render_schematic(format: &str, input_path: &str) -> Result<(), String> {
+    let format = match format {
+        "ansi" => day_03::RenderFormat::Ansi,
+        "html" => day_03::RenderFormat::Html,
+        "svg" => day_03::RenderFormat::Svg,
+        _ => return Err(format!("Unknown render format '{format}'")),
+    };
+    let input = read_input(input_path)?;
+    let grid = Grid::parse(&input).map_err(|e| format!("{input_path}: {e}"))?;
+    let schematic =
+        day_03::Schematic::from_grid(&grid).map_err(|e| format!("{input_path}: {e}"))?;
+
+    print!(
+        "{}",
+        day_03::render_schematic(&grid, &schematic, &day_03::GearRule::default(), format)
+    );
+
+    Ok(())
+}
+
+fn trace_cards(format: &str, input_path: &str) -> Result<(), String> {
+    let input = read_input(input_path)?;
+    let cards = day_04::parse_cards(&input).map_err(|e| format!("{input_path}: {e}"))?;
+    let trace = day_04::CascadeTrace::new(&cards).map_err(|e| e.to_string())?;
+
+    match format {
+        "table" => print!("{}", trace.to_table()),
+        "dot" => print!("{}", trace.to_dot()),
+        "animate" => trace
+            .animate(&mut io::stdout(), Duration::from_millis(300))
+            .map_err(|e| e.to_string())?,
+        _ => return Err(format!("Unknown trace format '{format}'")),
+    }
+
+    Ok(())
+}
+
+fn read_games(input_path: &str) -> Result<Vec<day_02::Game>, String> {
+    let input = read_input(input_path)?;
+    day_02::parse_games(&input).map_err(|e| format!("{input_path}: {e}"))
+}
+
+/// The input committed next to a day's solution, or else the one fetched
+/// into the input cache.
+fn default_input(day: u32) -> String {
+    let committed = format!("src/day_{day:02}/input.txt");
+    if Path::new(&committed).is_file() {
+        committed
+    } else {
+        site::InputCache::new(INPUTS_DIR)
+            .path(day)
+            .display()
+            .to_string()
+    }
+}
+
+fn read_input(input_path: &str) -> Result<String, String> {
+    fs::read_to_string(input_path).map_err(|e| format!("Cannot read {input_path}: {e}"))
+}
+
+/// Fails with a countdown if the puzzle of `day` has not unlocked yet.
+fn check_unlocked(day: u32) -> Result<(), String> {
+    let now = calendar::now();
+    match calendar::unlock_time(day) {
+        Some(unlock) if unlock > now => Err(format!(
+            "Day {day} of {} unlocks in {}",
+            calendar::event_label(),
+            calendar::format_duration(Duration::from_secs(unlock - now))
+        )),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(feature = "parallel")]
+use std::{num::NonZeroUsize, thread};
+
+/// Applies `f` to every item and returns the results in the order of
+/// `items`. With the `parallel` feature the items are split into one chunk
+/// per available thread; otherwise they are mapped on the calling thread.
+pub fn map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync,
+{
+    map_with_threads(items, threads(), f)
+}
+
+/// Like [`map`], but with an explicit number of threads.
+pub fn map_with_threads<T, U, F>(items: &[T], threads: usize, f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if threads > 1 && items.len() > 1 {
+        let chunk_size = items.len().div_ceil(threads);
+        let f = &f;
+        return thread::scope(|scope| {
+            let handles = items
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<U>>()))
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    let _ = threads;
+
+    items.iter().map(f).collect()
+}
+
+#[cfg(feature = "parallel")]
+fn threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+#[cfg(not(feature = "parallel"))]
+fn threads() -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_order() {
+        let items = (0..1000u64).collect::<Vec<_>>();
+        let expected = items.iter().map(|n| n * n).collect::<Vec<_>>();
+
+        for threads in [1, 2, 3, 7, 2000] {
+            assert_eq!(expected, map_with_threads(&items, threads, |n| n * n));
+        }
+        assert_eq!(expected, map(&items, |n| n * n));
+        assert!(map(&[] as &[u64], |n| n * n).is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+use std::{error::Error, fmt, io, str::FromStr};
+
+use itertools::Itertools;
+
+/// An input error at a 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// A line of input together with its 1-based line number. Parts of the line
+/// are passed around as [`Span`]s, so errors can point at the column where a
+/// part starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    number: usize,
+    text: &'a str,
+}
+
+/// A part of a line's text and the byte offset in the line where it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span<'a> {
+    start: usize,
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn new(number: usize, text: &'a str) -> Self {
+        Line { number, text }
+    }
+
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// The whole line as a span.
+    pub fn span(&self) -> Span<'a> {
+        Span {
+            start: 0,
+            text: self.text,
+        }
+    }
+
+    /// An error pointing at the byte offset `start` in the line. Offsets past
+    /// the end point just after the last character.
+    pub fn error(&self, start: usize, message: impl Into<String>) -> ParseError {
+        let prefix = self.text.get(..start).unwrap_or(self.text);
+
+        ParseError {
+            line: self.number,
+            column: prefix.chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Splits `span` at the first `separator` into two sections.
+    pub fn pair(
+        &self,
+        span: Span<'a>,
+        separator: char,
+    ) -> Result<(Span<'a>, Span<'a>), ParseError> {
+        span.split_once(separator).ok_or_else(|| {
+            self.error(
+                span.start,
+                format!("expected two parts separated by '{separator}'"),
+            )
+        })
+    }
+
+    /// Returns what follows `<label>:` at the start of the line.
+    pub fn after_label(&self, label: &str) -> Result<Span<'a>, ParseError> {
+        let text = self.text.trim_start();
+        text.strip_prefix(label)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .map(|rest| Span {
+                start: self.text.len() - rest.len(),
+                text: rest,
+            })
+            .ok_or_else(|| self.error(self.text.len() - text.len(), format!("expected '{label}:'")))
+    }
+
+    /// Parses a `<label> <id>: <rest>` header, returning the ID and the rest.
+    pub fn label_and_id(&self, label: &str) -> Result<(u32, Span<'a>), ParseError> {
+        let (header, rest) = self.pair(self.span(), ':')?;
+        let (found_label, id) = header
+            .split_whitespace()
+            .collect_tuple()
+            .ok_or_else(|| self.error(header.start, format!("expected '{label} <id>'")))?;
+        if found_label.text != label {
+            return Err(self.error(found_label.start, format!("expected '{label}'")));
+        }
+
+        Ok((self.integer(id)?, rest))
+    }
+
+    pub fn integer<T: FromStr>(&self, span: Span<'a>) -> Result<T, ParseError> {
+        let span = span.trim();
+        span.text
+            .parse()
+            .map_err(|_| self.error(span.start, format!("invalid number '{}'", span.text)))
+    }
+
+    /// Parses the whitespace-separated integers in `span`.
+    pub fn integers<T: FromStr>(&self, span: Span<'a>) -> Result<Vec<T>, ParseError> {
+        span.split_whitespace()
+            .map(|number| self.integer(number))
+            .collect()
+    }
+}
+
+impl<'a> Span<'a> {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn trim(self) -> Self {
+        let text = self.text.trim_start();
+        Span {
+            start: self.start + self.text.len() - text.len(),
+            text: text.trim_end(),
+        }
+    }
+
+    pub fn split(self, separator: char) -> impl Iterator<Item = Span<'a>> {
+        let mut start = self.start;
+        self.text.split(separator).map(move |text| {
+            let span = Span { start, text };
+            start += text.len() + separator.len_utf8();
+            span
+        })
+    }
+
+    /// The non-empty parts separated by ASCII whitespace.
+    pub fn split_whitespace(self) -> impl Iterator<Item = Span<'a>> {
+        let mut start = self.start;
+        self.text
+            .split(|c: char| c.is_ascii_whitespace())
+            .map(move |text| {
+                let span = Span { start, text };
+                // Every ASCII whitespace character is a single byte.
+                start += text.len() + 1;
+                span
+            })
+            .filter(|span| !span.text.is_empty())
+    }
+
+    pub fn split_once(self, separator: char) -> Option<(Span<'a>, Span<'a>)> {
+        let (left, right) = self.text.split_once(separator)?;
+        Some((
+            Span {
+                start: self.start,
+                text: left,
+            },
+            Span {
+                start: self.start + left.len() + separator.len_utf8(),
+                text: right,
+            },
+        ))
+    }
+}
+
+/// The lines of `input`, numbered from 1.
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, text)| Line::new(index + 1, text))
+}
+
+/// An error while reading input incrementally: either the reader failed or
+/// the input was rejected with `E`.
+#[derive(Debug)]
+pub enum ReadError<E = ParseError> {
+    Io(io::Error),
+    Input(E),
+}
+
+impl<E: fmt::Display> fmt::Display for ReadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "{error}"),
+            ReadError::Input(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for ReadError<E> {}
+
+/// Folds over the lines of `reader`, numbered from 1, holding only the
+/// current line in memory.
+pub fn try_fold_lines<R, T, E, F>(reader: R, init: T, mut f: F) -> Result<T, ReadError<E>>
+where
+    R: io::BufRead,
+    F: FnMut(T, Line) -> Result<T, E>,
+{
+    let mut accumulator = init;
+    for (index, text) in reader.lines().enumerate() {
+        let text = text.map_err(ReadError::Io)?;
+        accumulator = f(accumulator, Line::new(index + 1, &text)).map_err(ReadError::Input)?;
+    }
+    Ok(accumulator)
+}
+
+/// A group of consecutive non-blank lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    lines: Vec<Line<'a>>,
+}
+
+impl<'a> Block<'a> {
+    pub fn lines(&self) -> &[Line<'a>] {
+        &self.lines
+    }
+}
+
+/// Splits `input` into blocks separated by one or more blank lines.
+pub fn blocks(input: &str) -> Vec<Block<'_>> {
+    let mut blocks = vec![];
+    let mut current = vec![];
+
+    for line in lines(input) {
+        if line.text().trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push(Block { lines: current });
+                current = vec![];
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        blocks.push(Block { lines: current });
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_and_integers() {
+        let line = Line::new(3, "Card  12: 41 48 | 83 x6");
+        let (id, rest) = line.label_and_id("Card").unwrap();
+        assert_eq!(12, id);
+
+        let (winning, scratched) = line.pair(rest, '|').unwrap();
+        assert_eq!((9, " 41 48 "), (winning.start(), winning.text()));
+        assert_eq!(vec![41, 48], line.integers::<u32>(winning).unwrap());
+        assert_eq!(
+            ParseError {
+                line: 3,
+                column: 22,
+                message: "invalid number 'x6'".to_owned()
+            },
+            line.integers::<u32>(scratched).unwrap_err()
+        );
+
+        assert_eq!(1, line.label_and_id("Game").unwrap_err().column);
+        let times = Line::new(1, "  Time: 7  15").after_label("Time").unwrap();
+        assert_eq!((7, " 7  15"), (times.start(), times.text()));
+        assert_eq!(
+            3,
+            Line::new(1, "  Tim: 7")
+                .after_label("Time")
+                .unwrap_err()
+                .column
+        );
+        assert_eq!(
+            vec![(8, "7"), (11, "15")],
+            times
+                .split_whitespace()
+                .map(|span| (span.start(), span.text()))
+                .collect_vec()
+        );
+
+        // Only the first separator splits, and columns count characters.
+        let line = Line::new(1, "é: 1 | 2 | x");
+        let (_, rest) = line.pair(line.span(), ':').unwrap();
+        let (_, rest) = line.pair(rest, '|').unwrap();
+        assert_eq!(" 2 | x", rest.text());
+        assert_eq!(10, line.integers::<u32>(rest).unwrap_err().column);
+        assert_eq!(7, line.error(rest.start(), "").column);
+        assert_eq!(13, line.error(100, "").column);
+    }
+
+    #[test]
+    fn blank_line_delimited_blocks() {
+        let blocks = blocks("seeds: 1\r\n\r\n\r\na map:\r\n1 2 3\r\n");
+        assert_eq!(2, blocks.len());
+        assert_eq!(
+            vec![(4, "a map:"), (5, "1 2 3")],
+            blocks[1]
+                .lines()
+                .iter()
+                .map(|line| (line.number(), line.text()))
+                .collect_vec()
+        );
+    }
+}
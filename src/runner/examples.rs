@@ -0,0 +1,39 @@
+use crate::{day_01, day_02, day_03, day_04, day_05, day_06};
+
+/// An example from a puzzle's description, with the answer given for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Example {
+    pub day: u32,
+    pub part: u32,
+    pub input: &'static str,
+    pub answer: &'static str,
+}
+
+/// The examples of every puzzle the runner solves.
+pub const EXAMPLES: [Example; 12] = [
+    example(1, 1, day_01::EXAMPLE_PART_1, "142"),
+    example(1, 2, day_01::EXAMPLE_PART_2, "281"),
+    example(2, 1, day_02::EXAMPLE, "8"),
+    example(2, 2, day_02::EXAMPLE, "2286"),
+    example(3, 1, day_03::EXAMPLE, "4361"),
+    example(3, 2, day_03::EXAMPLE, "467835"),
+    example(4, 1, day_04::EXAMPLE, "13"),
+    example(4, 2, day_04::EXAMPLE, "30"),
+    example(5, 1, day_05::EXAMPLE, "35"),
+    example(5, 2, day_05::EXAMPLE, "46"),
+    example(6, 1, day_06::EXAMPLE, "288"),
+    example(6, 2, day_06::EXAMPLE, "71503"),
+];
+
+const fn example(day: u32, part: u32, input: &'static str, answer: &'static str) -> Example {
+    Example {
+        day,
+        part,
+        input,
+        answer,
+    }
+}
+
+pub fn examples(day: u32) -> impl Iterator<Item = &'static Example> {
+    EXAMPLES.iter().filter(move |example| example.day == day)
+}
@@ -0,0 +1,317 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead, Read},
+    time::Instant,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    calendar, day_01, day_02, day_03, day_04, day_05, day_06,
+    parse::{ParseError, ReadError},
+};
+
+mod examples;
+mod output;
+mod watch;
+
+pub use examples::{examples, Example, EXAMPLES};
+pub use output::{format_solutions, OutputFormat, Solution};
+pub use watch::{
+    check, decode_timings, encode_timings, format_report, timings, Case, Checked, Timings, Watcher,
+};
+
+#[derive(Debug)]
+pub enum RunError {
+    UnknownPuzzle {
+        day: u32,
+        part: u32,
+    },
+    /// The answer does not fit into a `u64`.
+    AnswerOverflow,
+    Io(io::Error),
+    Input(Box<dyn Error>),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::UnknownPuzzle { day, part } => {
+                write!(f, "no solution for day {day} part {part}")
+            }
+            RunError::AnswerOverflow => write!(f, "the answer overflows"),
+            RunError::Io(error) => write!(f, "{error}"),
+            RunError::Input(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for RunError {}
+
+impl<E: Error + 'static> From<ReadError<E>> for RunError {
+    fn from(error: ReadError<E>) -> Self {
+        match error {
+            ReadError::Io(error) => RunError::Io(error),
+            ReadError::Input(error) => RunError::Input(Box::new(error)),
+        }
+    }
+}
+
+/// Like [`solve`], but also times the solution and hashes the input.
+pub fn run(day: u32, part: u32, reader: impl BufRead) -> Result<Solution, RunError> {
+    let mut reader = HashingReader::new(reader);
+    let start = Instant::now();
+    let answer = solve(day, part, &mut reader)?;
+    let duration = start.elapsed();
+
+    // Hash any input the solution did not need to read.
+    io::copy(&mut reader, &mut io::sink()).map_err(RunError::Io)?;
+
+    Ok(Solution {
+        day,
+        part,
+        answer,
+        duration,
+        input_hash: reader.hash,
+    })
+}
+
+/// Passes input through while computing its 64-bit FNV-1a hash.
+struct HashingReader<R> {
+    inner: R,
+    hash: u64,
+}
+
+impl<R> HashingReader<R> {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    fn new(inner: R) -> Self {
+        HashingReader {
+            inner,
+            hash: Self::OFFSET_BASIS,
+        }
+    }
+
+    fn update(hash: u64, bytes: &[u8]) -> u64 {
+        bytes.iter().fold(hash, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(Self::PRIME)
+        })
+    }
+}
+
+impl<R: BufRead> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hash = Self::update(self.hash, &buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for HashingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        // The buffer is already filled, so this does not read.
+        if let Ok(buffer) = self.inner.fill_buf() {
+            self.hash = Self::update(self.hash, &buffer[..amount.min(buffer.len())]);
+        }
+        self.inner.consume(amount);
+    }
+}
+
+/// Solves one part of a day's puzzle for the input in `reader`. Days 1, 2
+/// and 4 are solved while reading line by line; the other days read the whole
+/// input first.
+pub fn solve(day: u32, part: u32, mut reader: impl BufRead) -> Result<String, RunError> {
+    let answer = match (day, part) {
+        (1, 1) => day_01::retrieve_calibration_value_part1_from_reader(reader)?.to_string(),
+        (1, 2) => day_01::retrieve_calibration_value_part2_from_reader(reader)?.to_string(),
+        (2, 1) => day_02::sum_ids_of_possible_games_from_reader(reader, &day_02::RGB_DICE_COUNTS)?
+            .to_string(),
+        (2, 2) => day_02::sum_power_of_minimum_dice_sets_from_reader(reader)?.to_string(),
+        (4, 1) => day_04::sum_scores_from_reader(reader, &day_04::Scoring::Doubling)?.to_string(),
+        (4, 2) => day_04::count_copies_from_reader::<u64>(reader)?.to_string(),
+        (3, 1) | (3, 2) | (5, 1) | (5, 2) | (6, 1) | (6, 2) => {
+            let mut input = String::new();
+            reader.read_to_string(&mut input).map_err(RunError::Io)?;
+            solve_in_memory(day, part, &input)?
+        }
+        _ => return Err(RunError::UnknownPuzzle { day, part }),
+    };
+
+    Ok(answer)
+}
+
+fn solve_in_memory(day: u32, part: u32, input: &str) -> Result<String, RunError> {
+    let input_error = |error: day_03::SchematicError| RunError::Input(Box::new(error));
+    let parse_error = |error: ParseError| RunError::Input(Box::new(error));
+    let no_seeds = || RunError::Input("the almanac has no seeds".into());
+
+    let answer = match (day, part) {
+        (3, 1) => day_03::Schematic::parse(input)
+            .map_err(input_error)?
+            .part_number_values(day_03::Counting::UniqueByPosition)
+            .into_iter()
+            .map(u64::from)
+            .sum::<u64>(),
+        (3, 2) => day_03::Schematic::parse(input)
+            .map_err(input_error)?
+            .gear_values(&day_03::GearRule::default())
+            .into_iter()
+            .try_fold(0u64, |sum, value| sum.checked_add(value?))
+            .ok_or(RunError::AnswerOverflow)?,
+        (5, 1) => day_05::get_lowest_location_per_seed(input)
+            .map_err(parse_error)?
+            .into_values()
+            .min()
+            .ok_or_else(no_seeds)?,
+        (5, 2) => day_05::get_lowest_location_of_seed_ranges(input)
+            .map_err(parse_error)?
+            .ok_or_else(no_seeds)?,
+        (6, 1) => day_06::get_number_of_ways_to_win_part1(input)
+            .map_err(parse_error)?
+            .into_iter()
+            .try_fold(1u64, |product, ways| product.checked_mul(ways as u64))
+            .ok_or(RunError::AnswerOverflow)?,
+        (6, 2) => day_06::get_number_of_ways_to_win_part2(input).map_err(parse_error)? as u64,
+        _ => return Err(RunError::UnknownPuzzle { day, part }),
+    };
+
+    Ok(answer.to_string())
+}
+
+/// The days and parts [`solve`] knows about, in order.
+pub fn puzzles() -> Vec<(u32, u32)> {
+    (1..=6).cartesian_product(1..=2).collect_vec()
+}
+
+/// The [`puzzles`] whose day has unlocked by `now`, in seconds since the
+/// Unix epoch.
+pub fn available_puzzles(now: u64) -> Vec<(u32, u32)> {
+    puzzles()
+        .into_iter()
+        .filter(|&(day, _)| calendar::is_unlocked(day, now))
+        .collect_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn solves_examples() {
+        let solve = |day, part, input: &str| solve(day, part, input.as_bytes()).unwrap();
+
+        assert_eq!(
+            "142",
+            solve(1, 1, "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet")
+        );
+        assert_eq!("1", solve(2, 1, "Game 1: 3 blue, 4 red\nGame 2: 20 red"));
+        assert_eq!("4361", solve(3, 1, "467..114..\n...*......\n..35..633.\n......#...\n617*......\n.....+.58.\n..592.....\n......755.\n...$.*....\n.664.598.."));
+        assert_eq!(
+            "288",
+            solve(6, 1, "Time:      7  15   30\nDistance:  9  40  200")
+        );
+        assert_eq!(12, puzzles().len());
+    }
+
+    #[test]
+    fn solves_every_example() {
+        for example in EXAMPLES {
+            assert_eq!(
+                example.answer,
+                solve(example.day, example.part, example.input.as_bytes()).unwrap(),
+                "day {} part {}",
+                example.day,
+                example.part
+            );
+        }
+        assert_eq!(
+            puzzles(),
+            EXAMPLES
+                .iter()
+                .map(|example| (example.day, example.part))
+                .collect_vec()
+        );
+        assert_eq!(2, examples(5).count());
+    }
+
+    #[test]
+    fn timed_and_hashed() {
+        let solution = run(1, 1, "a1\n".as_bytes()).unwrap();
+        assert_eq!(
+            (1, 1, "11"),
+            (solution.day, solution.part, solution.answer.as_str())
+        );
+        assert_eq!(0xe679_8319_04b4_8b77, solution.input_hash);
+        assert_eq!(
+            0xcbf2_9ce4_8422_2325,
+            run(3, 1, "".as_bytes()).unwrap().input_hash
+        );
+
+        let solutions = [
+            Solution {
+                duration: Duration::from_micros(1500),
+                ..solution.clone()
+            },
+            Solution {
+                part: 2,
+                answer: "a,\"b\"".to_owned(),
+                duration: Duration::from_micros(20),
+                ..solution
+            },
+        ];
+        assert_eq!(
+            "Advent of Code 12023 HE\nDay 1 part 1: 11 (1.50ms, input e679831904b48b77)\nDay 1 part 2: a,\"b\" (20.00µs, input e679831904b48b77)\n",
+            format_solutions(&solutions, OutputFormat::Plain)
+        );
+        assert_eq!(
+            r#"[{"day":1,"part":1,"answer":"11","duration_us":1500,"input_hash":"e679831904b48b77"},{"day":1,"part":2,"answer":"a,\"b\"","duration_us":20,"input_hash":"e679831904b48b77"}]
+"#,
+            format_solutions(&solutions, OutputFormat::Json)
+        );
+        assert_eq!(
+            "day,part,answer,duration_us,input_hash\n1,1,11,1500,e679831904b48b77\n1,2,\"a,\"\"b\"\"\",20,e679831904b48b77\n",
+            format_solutions(&solutions, OutputFormat::Csv)
+        );
+    }
+
+    #[test]
+    fn available_once_unlocked() {
+        let unlock = |day| calendar::unlock_time(day).unwrap();
+        assert!(available_puzzles(unlock(1) - 1).is_empty());
+        assert_eq!(
+            vec![(1, 1), (1, 2), (2, 1), (2, 2)],
+            available_puzzles(unlock(2))
+        );
+        assert_eq!(puzzles(), available_puzzles(unlock(25)));
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert!(matches!(
+            solve(7, 1, "".as_bytes()),
+            Err(RunError::UnknownPuzzle { day: 7, part: 1 })
+        ));
+        assert_eq!(
+            "line 1, column 1: expected 'Game'",
+            solve(2, 1, "Card 1: 3 blue".as_bytes())
+                .unwrap_err()
+                .to_string()
+        );
+
+        let no_seeds = day_05::EXAMPLE.replace("seeds: 79 14 55 13", "seeds:");
+        for part in [1, 2] {
+            assert_eq!(
+                "the almanac has no seeds",
+                solve(5, part, no_seeds.as_bytes()).unwrap_err().to_string()
+            );
+        }
+    }
+}
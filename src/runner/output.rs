@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::calendar;
+
+/// The answer to one part of a puzzle, with how long it took and a hash of
+/// the input it was computed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    pub answer: String,
+    pub duration: Duration,
+    /// The 64-bit FNV-1a hash of the input, which is stable across builds.
+    pub input_hash: u64,
+}
+
+/// A [`Solution`] as written to JSON.
+#[derive(Serialize)]
+struct JsonSolution<'a> {
+    day: u32,
+    part: u32,
+    answer: &'a str,
+    duration_us: u128,
+    input_hash: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Plain,
+    Json,
+    Csv,
+}
+
+/// Formats the solutions one per line under the event year, as a JSON array
+/// of objects or as CSV with a header. Durations are given in whole
+/// microseconds and hashes as 16 hexadecimal digits.
+pub fn format_solutions(solutions: &[Solution], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Plain => {
+            let lines = solutions
+                .iter()
+                .map(|solution| {
+                    format!(
+                        "Day {} part {}: {} ({:.2?}, input {:016x})\n",
+                        solution.day,
+                        solution.part,
+                        solution.answer,
+                        solution.duration,
+                        solution.input_hash
+                    )
+                })
+                .join("");
+            format!("Advent of Code {}\n{lines}", calendar::event_label())
+        }
+        OutputFormat::Json => {
+            let solutions = solutions
+                .iter()
+                .map(|solution| JsonSolution {
+                    day: solution.day,
+                    part: solution.part,
+                    answer: &solution.answer,
+                    duration_us: solution.duration.as_micros(),
+                    input_hash: format!("{:016x}", solution.input_hash),
+                })
+                .collect_vec();
+            let json = serde_json::to_string(&solutions).expect("solutions serialize to JSON");
+            format!("{json}\n")
+        }
+        OutputFormat::Csv => {
+            let mut csv = "day,part,answer,duration_us,input_hash\n".to_owned();
+            for solution in solutions {
+                csv.push_str(&format!(
+                    "{},{},{},{},{:016x}\n",
+                    solution.day,
+                    solution.part,
+                    escape_csv(&solution.answer),
+                    solution.duration.as_micros(),
+                    solution.input_hash
+                ));
+            }
+            csv
+        }
+    }
+}
+
+fn escape_csv(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use itertools::Itertools;
+
+use super::run;
+
+type Snapshot = BTreeMap<PathBuf, (SystemTime, u64)>;
+
+/// Notices changes to files, or to any file below a directory, by polling
+/// their modification times and sizes.
+#[derive(Debug, Clone)]
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    snapshot: Snapshot,
+}
+
+impl Watcher {
+    pub fn new<P: Into<PathBuf>>(paths: impl IntoIterator<Item = P>) -> Self {
+        let paths = paths.into_iter().map(Into::into).collect_vec();
+        let snapshot = snapshot(&paths);
+        Watcher { paths, snapshot }
+    }
+
+    /// Whether a watched file was added, removed or modified since the last
+    /// call, or since the watcher was created.
+    pub fn changed(&mut self) -> bool {
+        let snapshot = snapshot(&self.paths);
+        let changed = snapshot != self.snapshot;
+        self.snapshot = snapshot;
+        changed
+    }
+}
+
+fn snapshot(paths: &[PathBuf]) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    for path in paths {
+        visit(path, &mut snapshot);
+    }
+    snapshot
+}
+
+fn visit(path: &Path, snapshot: &mut Snapshot) {
+    let Ok(metadata) = fs::metadata(path) else {
+        return;
+    };
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+            visit(&entry.path(), snapshot);
+        }
+    } else if let Ok(modified) = metadata.modified() {
+        snapshot.insert(path.to_owned(), (modified, metadata.len()));
+    }
+}
+
+/// An input to solve one part of a day with, and the answer it should give
+/// if that is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    /// What the input is, e.g. "example" or "input".
+    pub name: String,
+    pub part: u32,
+    pub input: String,
+    pub expected: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checked {
+    pub name: String,
+    pub part: u32,
+    /// The answer, or why there is none.
+    pub answer: Result<String, String>,
+    pub expected: Option<String>,
+    pub duration: Duration,
+}
+
+impl Checked {
+    /// `None` if there was no answer to compare with.
+    pub fn passed(&self) -> Option<bool> {
+        match (&self.answer, &self.expected) {
+            (Err(_), _) => Some(false),
+            (Ok(answer), Some(expected)) => Some(answer == expected),
+            (Ok(_), None) => None,
+        }
+    }
+}
+
+/// How long each case, by name and part, took to solve.
+pub type Timings = BTreeMap<(String, u32), Duration>;
+
+/// Solves every case of `day`.
+pub fn check(day: u32, cases: &[Case]) -> Vec<Checked> {
+    cases
+        .iter()
+        .map(|case| {
+            let solution = run(day, case.part, case.input.as_bytes());
+            Checked {
+                name: case.name.clone(),
+                part: case.part,
+                duration: solution
+                    .as_ref()
+                    .map_or(Duration::ZERO, |solution| solution.duration),
+                answer: solution
+                    .map(|solution| solution.answer)
+                    .map_err(|error| error.to_string()),
+                expected: case.expected.clone(),
+            }
+        })
+        .collect_vec()
+}
+
+/// The durations of the cases that were solved.
+pub fn timings(checked: &[Checked]) -> Timings {
+    checked
+        .iter()
+        .filter(|checked| checked.answer.is_ok())
+        .map(|checked| ((checked.name.clone(), checked.part), checked.duration))
+        .collect()
+}
+
+/// The timings as `name:part:nanoseconds`, separated by commas.
+pub fn encode_timings(timings: &Timings) -> String {
+    timings
+        .iter()
+        .map(|((name, part), duration)| format!("{name}:{part}:{}", duration.as_nanos()))
+        .join(",")
+}
+
+/// Reads timings written by [`encode_timings`], skipping malformed entries.
+pub fn decode_timings(text: &str) -> Timings {
+    text.split(',')
+        .filter_map(|entry| {
+            let (name, part, nanos) = entry.split(':').collect_tuple()?;
+            Some((
+                (name.to_owned(), part.parse().ok()?),
+                Duration::from_nanos(nanos.parse().ok()?),
+            ))
+        })
+        .collect()
+}
+
+/// A summary line followed by one line per case, showing how much slower
+/// (`+`) or faster (`-`) each case was than in `previous`.
+pub fn format_report(day: u32, checked: &[Checked], previous: &Timings) -> String {
+    let counts = checked.iter().counts_by(Checked::passed);
+    let count = |passed| counts.get(&passed).copied().unwrap_or(0);
+    let width = checked
+        .iter()
+        .map(|checked| checked.name.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut report = format!(
+        "Day {day}: {} passed, {} failed, {} unchecked\n",
+        count(Some(true)),
+        count(Some(false)),
+        count(None)
+    );
+    for checked in checked {
+        let result = match (&checked.answer, &checked.expected) {
+            (Err(error), _) => format!("error: {error}"),
+            (Ok(answer), Some(expected)) if answer == expected => format!("pass {answer}"),
+            (Ok(answer), Some(expected)) => format!("FAIL {answer}, expected {expected}"),
+            (Ok(answer), None) => answer.clone(),
+        };
+        let timing = match previous.get(&(checked.name.clone(), checked.part)) {
+            _ if checked.answer.is_err() => String::new(),
+            Some(&before) if checked.duration >= before => {
+                format!(
+                    " ({:.2?}, +{:.2?})",
+                    checked.duration,
+                    checked.duration - before
+                )
+            }
+            Some(&before) => {
+                format!(
+                    " ({:.2?}, -{:.2?})",
+                    checked.duration,
+                    before - checked.duration
+                )
+            }
+            None => format!(" ({:.2?})", checked.duration),
+        };
+        report.push_str(&format!(
+            "  {:width$} part {}: {result}{timing}\n",
+            checked.name, checked.part
+        ));
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn notices_changes() {
+        let dir = env::temp_dir().join(format!("aoc-watch-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        let file = dir.join("input.txt");
+        fs::write(&file, "1").unwrap();
+
+        let mut watcher = Watcher::new([&dir]);
+        assert!(!watcher.changed());
+        fs::write(&file, "12").unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+        fs::write(dir.join("nested").join("new.txt"), "").unwrap();
+        assert!(watcher.changed());
+        fs::remove_file(&file).unwrap();
+        assert!(watcher.changed());
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+    }
+
+    #[test]
+    fn checks_cases() {
+        let case = |name: &str, input: &str, expected: Option<&str>| Case {
+            name: name.to_owned(),
+            part: 1,
+            input: input.to_owned(),
+            expected: expected.map(str::to_owned),
+        };
+        let checked = check(
+            1,
+            &[
+                case("example", "a1\nb2\n", Some("33")),
+                case("wrong", "a1\n", Some("12")),
+                case("input", "7\n", None),
+                case("broken", "a\n", None),
+            ],
+        );
+
+        assert_eq!(
+            vec![Some(true), Some(false), None, Some(false)],
+            checked.iter().map(Checked::passed).collect_vec()
+        );
+        assert_eq!(
+            Err("line 1, column 1: expected a digit".to_owned()),
+            checked[3].answer
+        );
+        assert_eq!(
+            vec![("example", 1), ("input", 1), ("wrong", 1)],
+            timings(&checked)
+                .keys()
+                .map(|(name, part)| (name.as_str(), *part))
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn reports() {
+        let checked =
+            |name: &str, answer: Result<&str, &str>, expected: Option<&str>, micros| Checked {
+                name: name.to_owned(),
+                part: 1,
+                answer: answer.map(str::to_owned).map_err(str::to_owned),
+                expected: expected.map(str::to_owned),
+                duration: Duration::from_micros(micros),
+            };
+        let checked = [
+            checked("example", Ok("142"), Some("142"), 12),
+            checked("input", Ok("7"), Some("8"), 1500),
+            checked("extra", Ok("3"), None, 40),
+            checked("broken", Err("bad input"), None, 0),
+        ];
+        let previous = Timings::from([
+            (("example".to_owned(), 1), Duration::from_micros(10)),
+            (("input".to_owned(), 1), Duration::from_micros(2000)),
+            (("broken".to_owned(), 1), Duration::from_micros(5)),
+        ]);
+
+        assert_eq!(
+            "Day 1: 1 passed, 2 failed, 1 unchecked
+  example part 1: pass 142 (12.00µs, +2.00µs)
+  input   part 1: FAIL 7, expected 8 (1.50ms, -500.00µs)
+  extra   part 1: 3 (40.00µs)
+  broken  part 1: error: bad input
+",
+            format_report(1, &checked, &previous)
+        );
+        assert_eq!(previous, decode_timings(&encode_timings(&previous)));
+        assert_eq!(
+            Timings::from([(("input".to_owned(), 2), Duration::from_nanos(7))]),
+            decode_timings("input:2:7,input:x:1,bad,")
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{check_day, Client, SiteError, YEAR};
+
+/// Puzzle inputs downloaded before, stored as `day_XX.txt` in a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputCache {
+    dir: PathBuf,
+}
+
+impl InputCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        InputCache { dir: dir.into() }
+    }
+
+    pub fn path(&self, day: u32) -> PathBuf {
+        self.dir.join(format!("day_{day:02}.txt"))
+    }
+
+    pub fn contains(&self, day: u32) -> bool {
+        self.path(day).is_file()
+    }
+
+    /// Stores `input` through a temporary file, so an interrupted download
+    /// never leaves a partial input behind.
+    fn store(&self, day: u32, input: &str) -> Result<PathBuf, SiteError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(day);
+        let partial = path.with_extension("partial");
+        fs::write(&partial, input)?;
+        fs::rename(&partial, &path)?;
+        Ok(path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    /// The input was already cached; the site was not contacted.
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+impl Fetched {
+    pub fn path(&self) -> &Path {
+        match self {
+            Fetched::Cached(path) | Fetched::Downloaded(path) => path,
+        }
+    }
+}
+
+/// Returns the cached input of `day`, downloading it first if needed.
+pub fn fetch_input(client: &Client, cache: &InputCache, day: u32) -> Result<Fetched, SiteError> {
+    check_day(day)?;
+    if cache.contains(day) {
+        return Ok(Fetched::Cached(cache.path(day)));
+    }
+
+    let input = client.get(&format!("/{YEAR}/day/{day}/input"))?;
+    Ok(Fetched::Downloaded(cache.store(day, &input)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::site::mock::Server;
+
+    #[test]
+    fn downloads_once() {
+        let server = Server::start(|request| match request.path.as_str() {
+            "/2023/day/4/input" => (200, "Card 1: 1 | 1\n".to_owned()),
+            _ => (404, "Not Found".to_owned()),
+        });
+        let client = Client::new(server.url.clone(), Some("secret".to_owned()));
+        let dir = env::temp_dir().join(format!("aoc-fetch-{}", std::process::id()));
+        let cache = InputCache::new(&dir);
+
+        let fetched = fetch_input(&client, &cache, 4).unwrap();
+        assert_eq!(Fetched::Downloaded(dir.join("day_04.txt")), fetched);
+        assert_eq!(
+            "Card 1: 1 | 1\n",
+            fs::read_to_string(fetched.path()).unwrap()
+        );
+        assert_eq!(
+            Fetched::Cached(dir.join("day_04.txt")),
+            fetch_input(&client, &cache, 4).unwrap()
+        );
+        assert_eq!(1, server.requests().len());
+        assert_eq!(
+            Some("session=secret"),
+            server.requests()[0].cookie.as_deref()
+        );
+
+        assert!(matches!(
+            fetch_input(&client, &cache, 5),
+            Err(SiteError::Http { status: 404, .. })
+        ));
+        assert!(!cache.contains(5));
+        assert!(matches!(
+            fetch_input(&client, &cache, 26),
+            Err(SiteError::InvalidDay(26))
+        ));
+        assert!(matches!(
+            fetch_input(&Client::new(server.url.clone(), None), &cache, 6),
+            Err(SiteError::MissingSession)
+        ));
+        assert_eq!(2, server.requests().len());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
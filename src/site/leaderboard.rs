@@ -0,0 +1,286 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use itertools::Itertools;
+use serde::Deserialize;
+
+use super::{check_day, Client, SiteError, YEAR};
+use crate::calendar;
+
+/// A private leaderboard in the site's JSON export format.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Leaderboard {
+    pub event: String,
+    pub owner_id: u64,
+    pub members: BTreeMap<u64, Member>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Member {
+    pub id: u64,
+    /// `None` for anonymous users.
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u64,
+    #[serde(default)]
+    pub last_star_ts: u64,
+    /// The stars by day and part.
+    #[serde(default)]
+    pub completion_day_level: BTreeMap<u32, BTreeMap<u32, Star>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Star {
+    /// When the star was earned, in seconds since the Unix epoch.
+    pub get_star_ts: u64,
+}
+
+impl Leaderboard {
+    /// Parses an export of the [`YEAR`] event; completion times are measured
+    /// from that event's unlock times.
+    pub fn parse(json: &str) -> Result<Self, SiteError> {
+        let leaderboard: Self = serde_json::from_str(json)
+            .map_err(|error| SiteError::InvalidLeaderboard(error.to_string()))?;
+        if leaderboard.event != YEAR.to_string() {
+            return Err(SiteError::InvalidLeaderboard(format!(
+                "expected event {YEAR} but found {}",
+                leaderboard.event
+            )));
+        }
+        Ok(leaderboard)
+    }
+
+    /// The members by local score, then stars, then who got their last star
+    /// first.
+    pub fn standings(&self) -> Vec<&Member> {
+        self.members
+            .values()
+            .sorted_by_key(|member| {
+                (
+                    std::cmp::Reverse(member.local_score),
+                    std::cmp::Reverse(member.stars),
+                    member.last_star_ts,
+                    member.id,
+                )
+            })
+            .collect_vec()
+    }
+}
+
+impl Member {
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    /// Seconds from the puzzle unlocking until the star of `part` was earned.
+    pub fn completion_time(&self, day: u32, part: u32) -> Option<u64> {
+        let star = self.completion_day_level.get(&day)?.get(&part)?;
+        star.get_star_ts.checked_sub(calendar::unlock_time(day)?)
+    }
+}
+
+/// Downloads the private leaderboard `id` of [`YEAR`].
+pub fn fetch_leaderboard(client: &Client, id: u64) -> Result<Leaderboard, SiteError> {
+    Leaderboard::parse(&client.get(&format!("/{YEAR}/leaderboard/private/view/{id}.json"))?)
+}
+
+/// The standings with one column per day: `*` for both stars, `+` for the
+/// first only and `.` for none.
+pub fn standings_table(leaderboard: &Leaderboard) -> String {
+    let rows = leaderboard
+        .standings()
+        .into_iter()
+        .enumerate()
+        .map(|(index, member)| {
+            let days = (1..=25)
+                .map(|day| match member.completion_day_level.get(&day) {
+                    Some(parts) if parts.len() >= 2 => '*',
+                    Some(_) => '+',
+                    None => '.',
+                })
+                .collect::<String>();
+            vec![
+                (index + 1).to_string(),
+                member.local_score.to_string(),
+                member.stars.to_string(),
+                days,
+                member.display_name(),
+            ]
+        })
+        .collect_vec();
+
+    render_table(&["Rank", "Score", "Stars", "Days 1-25", "Name"], &rows)
+}
+
+/// How long after unlocking each member solved both parts of `day`, and the
+/// time between the parts, fastest second star first.
+pub fn day_table(leaderboard: &Leaderboard, day: u32) -> Result<String, SiteError> {
+    check_day(day)?;
+    let rows = leaderboard
+        .members
+        .values()
+        .filter_map(|member| {
+            let part_1 = member.completion_time(day, 1)?;
+            Some((member, part_1, member.completion_time(day, 2)))
+        })
+        .sorted_by_key(|&(member, part_1, part_2)| (part_2.unwrap_or(u64::MAX), part_1, member.id))
+        .enumerate()
+        .map(|(index, (member, part_1, part_2))| {
+            let elapsed = |seconds| calendar::format_duration(Duration::from_secs(seconds));
+            let optional = |seconds: Option<u64>| seconds.map_or("-".to_owned(), elapsed);
+            vec![
+                (index + 1).to_string(),
+                elapsed(part_1),
+                optional(part_2),
+                optional(part_2.map(|part_2| part_2.saturating_sub(part_1))),
+                member.display_name(),
+            ]
+        })
+        .collect_vec();
+
+    Ok(render_table(
+        &["Rank", "Part 1", "Part 2", "Delta", "Name"],
+        &rows,
+    ))
+}
+
+/// Right-aligns every column but the last, which holds the names.
+fn render_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let widths = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect_vec();
+    let line = |cells: Vec<&str>| {
+        let (name, aligned) = cells.split_last().unwrap();
+        let aligned = aligned
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:>width$}"))
+            .join("  ");
+        format!("{aligned}  {name}\n")
+    };
+
+    line(header.to_vec())
+        + &rows
+            .iter()
+            .map(|row| line(row.iter().map(String::as_str).collect()))
+            .join("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::mock::Server;
+
+    const EXPORT: &str = r#"{
+        "event": "2023",
+        "owner_id": 1,
+        "members": {
+            "1": {
+                "id": 1, "name": "Ada", "stars": 3, "local_score": 10, "global_score": 0,
+                "last_star_ts": 1701497000,
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": 1701407100, "star_index": 0},
+                        "2": {"get_star_ts": 1701407400, "star_index": 1}
+                    },
+                    "2": {"1": {"get_star_ts": 1701497000, "star_index": 2}}
+                }
+            },
+            "7": {
+                "id": 7, "name": null, "stars": 2, "local_score": 10, "global_score": 0,
+                "last_star_ts": 1701496000,
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": 1701406860, "star_index": 0},
+                        "2": {"get_star_ts": 1701500000, "star_index": 1}
+                    }
+                }
+            },
+            "9": {
+                "id": 9, "name": "Grace", "stars": 0, "local_score": 0, "global_score": 0,
+                "last_star_ts": 0, "completion_day_level": {}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn parses_the_export() {
+        let leaderboard = Leaderboard::parse(EXPORT).unwrap();
+        assert_eq!(3, leaderboard.members.len());
+        let anonymous = &leaderboard.members[&7];
+        assert_eq!("(anonymous user #7)", anonymous.display_name());
+        assert_eq!(Some(60), anonymous.completion_time(1, 1));
+        assert_eq!(None, anonymous.completion_time(2, 1));
+        assert_eq!(
+            vec![1, 7, 9],
+            leaderboard
+                .standings()
+                .iter()
+                .map(|member| member.id)
+                .collect_vec()
+        );
+
+        assert!(matches!(
+            Leaderboard::parse(r#"{"event": "2023"}"#),
+            Err(SiteError::InvalidLeaderboard(_))
+        ));
+        assert_eq!(
+            "invalid leaderboard: expected event 2023 but found 2022",
+            Leaderboard::parse(&EXPORT.replace(r#""event": "2023""#, r#""event": "2022""#))
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let leaderboard = Leaderboard::parse(EXPORT).unwrap();
+        assert_eq!(
+            "Rank  Score  Stars                  Days 1-25  Name
+   1     10      3  *+.......................  Ada
+   2     10      2  *........................  (anonymous user #7)
+   3      0      0  .........................  Grace
+",
+            standings_table(&leaderboard)
+        );
+        assert_eq!(
+            "Rank    Part 1       Part 2        Delta  Name
+   1  00:05:00     00:10:00     00:05:00  Ada
+   2  00:01:00  1d 01:53:20  1d 01:52:20  (anonymous user #7)
+",
+            day_table(&leaderboard, 1).unwrap()
+        );
+        assert_eq!(
+            "Rank    Part 1  Part 2  Delta  Name
+   1  01:03:20       -      -  Ada
+",
+            day_table(&leaderboard, 2).unwrap()
+        );
+    }
+
+    #[test]
+    fn fetches_from_an_endpoint() {
+        let server = Server::start(|request| match request.path.as_str() {
+            "/2023/leaderboard/private/view/1.json" => (200, EXPORT.to_owned()),
+            _ => (404, "Not Found".to_owned()),
+        });
+        let client = Client::new(server.url.clone(), Some("secret".to_owned()));
+
+        assert_eq!(
+            Leaderboard::parse(EXPORT).unwrap(),
+            fetch_leaderboard(&client, 1).unwrap()
+        );
+        assert!(matches!(
+            fetch_leaderboard(&client, 2),
+            Err(SiteError::Http { status: 404, .. })
+        ));
+    }
+}
@@ -0,0 +1,255 @@
+use std::{env, error::Error, fmt, fs, io, path::PathBuf};
+
+use crate::{calendar, parse::ParseError};
+
+mod fetch;
+mod leaderboard;
+mod submit;
+
+pub use fetch::{fetch_input, Fetched, InputCache};
+pub use leaderboard::{day_table, fetch_leaderboard, standings_table, Leaderboard, Member, Star};
+pub use submit::{submit_answer, AnswerStore, Attempt, Outcome};
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+/// The event year in the Gregorian calendar, as the site numbers it.
+pub const YEAR: u32 = calendar::to_gregorian(calendar::EVENT_YEAR) as u32;
+
+#[derive(Debug)]
+pub enum SiteError {
+    /// No session token was found in `AOC_SESSION` or the session file.
+    MissingSession,
+    InvalidDay(u32),
+    InvalidPart(u32),
+    /// The answer was not submitted, as earlier outcomes rule it out.
+    KnownWrong {
+        answer: String,
+        reason: String,
+    },
+    /// The site's reply to a submission could not be understood.
+    UnexpectedPage,
+    /// The leaderboard export could not be parsed.
+    InvalidLeaderboard(String),
+    /// The answers store could not be read.
+    Store(ParseError),
+    /// The site answered with an error status.
+    Http {
+        status: u16,
+        message: String,
+    },
+    /// The site could not be reached.
+    Transport(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for SiteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SiteError::MissingSession => write!(
+                f,
+                "no session token: set AOC_SESSION or write it to {}",
+                session_file().map_or("~/.config/aoc/session".into(), |path| path
+                    .display()
+                    .to_string())
+            ),
+            SiteError::InvalidDay(day) => write!(f, "there is no day {day}"),
+            SiteError::InvalidPart(part) => write!(f, "there is no part {part}"),
+            SiteError::KnownWrong { answer, reason } => {
+                write!(f, "not submitting {answer}: {reason}")
+            }
+            SiteError::UnexpectedPage => write!(f, "the site's reply was not understood"),
+            SiteError::InvalidLeaderboard(message) => write!(f, "invalid leaderboard: {message}"),
+            SiteError::Store(error) => write!(f, "answers store, {error}"),
+            SiteError::Http { status, message } => write!(f, "HTTP {status}: {message}"),
+            SiteError::Transport(message) => write!(f, "{message}"),
+            SiteError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for SiteError {}
+
+impl From<io::Error> for SiteError {
+    fn from(error: io::Error) -> Self {
+        SiteError::Io(error)
+    }
+}
+
+impl From<ureq::Error> for SiteError {
+    fn from(error: ureq::Error) -> Self {
+        match error {
+            ureq::Error::Status(status, response) => SiteError::Http {
+                status,
+                message: response
+                    .into_string()
+                    .map(|body| body.trim().to_owned())
+                    .unwrap_or_default(),
+            },
+            ureq::Error::Transport(error) => SiteError::Transport(error.to_string()),
+        }
+    }
+}
+
+/// Talks to the puzzle site, authenticated with a session token.
+pub struct Client {
+    base_url: String,
+    session: Option<String>,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>, session: Option<String>) -> Self {
+        Client {
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+            session,
+            agent: ureq::AgentBuilder::new()
+                .user_agent(concat!("aoc_12023/", env!("CARGO_PKG_VERSION")))
+                .build(),
+        }
+    }
+
+    /// A client for the URL in `AOC_URL`, or the real site, with the session
+    /// token from `AOC_SESSION` or the session file.
+    pub fn from_env() -> Self {
+        let base_url = env::var("AOC_URL").unwrap_or_else(|_| BASE_URL.to_owned());
+        let session = env::var("AOC_SESSION")
+            .ok()
+            .or_else(|| fs::read_to_string(session_file()?).ok())
+            .map(|session| session.trim().to_owned())
+            .filter(|session| !session.is_empty());
+
+        Client::new(base_url, session)
+    }
+
+    pub(crate) fn get(&self, path: &str) -> Result<String, SiteError> {
+        let response = self
+            .agent
+            .get(&format!("{}{path}", self.base_url))
+            .set("Cookie", &self.cookie()?)
+            .call()?;
+
+        Ok(response.into_string()?)
+    }
+
+    pub(crate) fn post_form(
+        &self,
+        path: &str,
+        fields: &[(&str, &str)],
+    ) -> Result<String, SiteError> {
+        let response = self
+            .agent
+            .post(&format!("{}{path}", self.base_url))
+            .set("Cookie", &self.cookie()?)
+            .send_form(fields)?;
+
+        Ok(response.into_string()?)
+    }
+
+    fn cookie(&self) -> Result<String, SiteError> {
+        let session = self.session.as_ref().ok_or(SiteError::MissingSession)?;
+        Ok(format!("session={session}"))
+    }
+}
+
+/// `~/.config/aoc/session`, holding nothing but the session token.
+pub fn session_file() -> Option<PathBuf> {
+    let home = env::var_os("HOME")?;
+    Some(PathBuf::from(home).join(".config/aoc/session"))
+}
+
+fn check_day(day: u32) -> Result<(), SiteError> {
+    match day {
+        1..=25 => Ok(()),
+        _ => Err(SiteError::InvalidDay(day)),
+    }
+}
+
+/// A stand-in for the puzzle site that answers requests with canned
+/// responses, for testing without network access.
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    /// A request as received by the [`Server`].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Request {
+        pub method: String,
+        pub path: String,
+        pub cookie: Option<String>,
+        pub body: String,
+    }
+
+    pub struct Server {
+        pub url: String,
+        requests: Arc<Mutex<Vec<Request>>>,
+    }
+
+    impl Server {
+        /// Serves `respond(request) -> (status, body)` until the test ends.
+        pub fn start(respond: impl Fn(&Request) -> (u16, String) + Send + 'static) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(vec![]));
+            let received = Arc::clone(&requests);
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = stream.unwrap();
+                    let request = read_request(&mut BufReader::new(&mut stream));
+                    let (status, body) = respond(&request);
+                    received.lock().unwrap().push(request);
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {status} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                }
+            });
+
+            Server { url, requests }
+        }
+
+        pub fn requests(&self) -> Vec<Request> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    fn read_request(reader: &mut impl BufRead) -> Request {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_owned();
+        let path = parts.next().unwrap_or_default().to_owned();
+
+        let mut cookie = None;
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = header.split_once(':').unwrap();
+            match name.to_ascii_lowercase().as_str() {
+                "cookie" => cookie = Some(value.trim().to_owned()),
+                "content-length" => length = value.trim().parse().unwrap(),
+                _ => {}
+            }
+        }
+
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+
+        Request {
+            method,
+            path,
+            cookie,
+            body: String::from_utf8(body).unwrap(),
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use std::{
+    fmt, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::parse::{self, ParseError};
+
+use super::{check_day, Client, SiteError, YEAR};
+
+/// What the site made of a submitted answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    Incorrect,
+    TooHigh,
+    TooLow,
+    /// An answer was submitted too recently; `wait` is how long until the
+    /// next one is accepted, if the page said so.
+    RateLimited {
+        wait: Option<Duration>,
+    },
+    /// The part is already solved or not unlocked yet.
+    WrongLevel,
+}
+
+impl Outcome {
+    /// Reads the outcome from the page returned for a submission.
+    pub fn parse(page: &str) -> Option<Outcome> {
+        if page.contains("That's the right answer") {
+            Some(Outcome::Correct)
+        } else if page.contains("That's not the right answer") {
+            Some(if page.contains("your answer is too high") {
+                Outcome::TooHigh
+            } else if page.contains("your answer is too low") {
+                Outcome::TooLow
+            } else {
+                Outcome::Incorrect
+            })
+        } else if page.contains("You gave an answer too recently") {
+            Some(Outcome::RateLimited {
+                wait: parse_wait(page),
+            })
+        } else if page.contains("You don't seem to be solving the right level") {
+            Some(Outcome::WrongLevel)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the outcome settles if the answer is right, so it is worth
+    /// remembering.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Outcome::Correct | Outcome::Incorrect | Outcome::TooHigh | Outcome::TooLow
+        )
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        match self {
+            Outcome::Correct => Some("correct"),
+            Outcome::Incorrect => Some("incorrect"),
+            Outcome::TooHigh => Some("too-high"),
+            Outcome::TooLow => Some("too-low"),
+            _ => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Outcome> {
+        [
+            Outcome::Correct,
+            Outcome::Incorrect,
+            Outcome::TooHigh,
+            Outcome::TooLow,
+        ]
+        .into_iter()
+        .find(|outcome| outcome.name() == Some(name))
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "correct"),
+            Outcome::Incorrect => write!(f, "incorrect"),
+            Outcome::TooHigh => write!(f, "too high"),
+            Outcome::TooLow => write!(f, "too low"),
+            Outcome::RateLimited { wait: Some(wait) } => {
+                write!(f, "rate limited, wait {}s", wait.as_secs())
+            }
+            Outcome::RateLimited { wait: None } => write!(f, "rate limited"),
+            Outcome::WrongLevel => write!(f, "already solved or not unlocked"),
+        }
+    }
+}
+
+/// Reads "You have 1m 5s left to wait".
+fn parse_wait(page: &str) -> Option<Duration> {
+    let (_, rest) = page.split_once("You have ")?;
+    let (wait, _) = rest.split_once(" left to wait")?;
+
+    wait.split_whitespace()
+        .try_fold(0u64, |total, part| {
+            let (number, seconds) = [('h', 3600), ('m', 60), ('s', 1)]
+                .into_iter()
+                .find_map(|(unit, seconds)| Some((part.strip_suffix(unit)?, seconds)))?;
+            total.checked_add(number.parse::<u64>().ok()?.checked_mul(seconds)?)
+        })
+        .map(Duration::from_secs)
+}
+
+/// A submitted answer and what the site said about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub day: u32,
+    pub part: u32,
+    pub answer: String,
+    pub outcome: Outcome,
+}
+
+/// The final outcomes of earlier submissions, kept in a tab-separated file
+/// of day, part, answer and outcome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnswerStore {
+    path: PathBuf,
+    attempts: Vec<Attempt>,
+}
+
+impl AnswerStore {
+    /// Loads the store at `path`, which need not exist yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, SiteError> {
+        let path = path.into();
+        let attempts = match fs::read_to_string(&path) {
+            Ok(text) => parse_attempts(&text).map_err(SiteError::Store)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(AnswerStore { path, attempts })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+
+    pub fn correct_answer(&self, day: u32, part: u32) -> Option<&str> {
+        self.attempts_for(day, part)
+            .find(|attempt| attempt.outcome == Outcome::Correct)
+            .map(|attempt| attempt.answer.as_str())
+    }
+
+    /// Why `answer` is known to be wrong, if it is: it was rejected before,
+    /// lies beyond an answer that was too high or too low, or differs from
+    /// the correct answer.
+    pub fn known_wrong(&self, day: u32, part: u32, answer: &str) -> Option<String> {
+        if let Some(correct) = self.correct_answer(day, part) {
+            return (correct != answer).then(|| format!("the correct answer is {correct}"));
+        }
+
+        let value = answer.parse::<i128>().ok();
+        self.attempts_for(day, part).find_map(|attempt| {
+            let bound = attempt.answer.parse::<i128>().ok();
+            let ruled_out = attempt.answer == answer
+                || match (attempt.outcome, value, bound) {
+                    (Outcome::TooHigh, Some(value), Some(bound)) => value >= bound,
+                    (Outcome::TooLow, Some(value), Some(bound)) => value <= bound,
+                    _ => false,
+                };
+            ruled_out.then(|| format!("{} was {}", attempt.answer, attempt.outcome))
+        })
+    }
+
+    /// Remembers a final outcome, appending it to the file.
+    pub fn record(&mut self, attempt: Attempt) -> Result<(), SiteError> {
+        let Some(name) = attempt.outcome.name() else {
+            return Ok(());
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(
+            file,
+            "{}\t{}\t{}\t{name}",
+            attempt.day, attempt.part, attempt.answer
+        )?;
+
+        self.attempts.push(attempt);
+        Ok(())
+    }
+
+    fn attempts_for(&self, day: u32, part: u32) -> impl Iterator<Item = &Attempt> {
+        self.attempts
+            .iter()
+            .filter(move |attempt| (attempt.day, attempt.part) == (day, part))
+    }
+}
+
+fn parse_attempts(text: &str) -> Result<Vec<Attempt>, ParseError> {
+    parse::lines(text)
+        .filter(|line| !line.text().trim().is_empty())
+        .map(|line| {
+            let fields = line.span().split('\t').collect::<Vec<_>>();
+            let [day, part, answer, outcome] = fields[..] else {
+                return Err(line.error(0, "expected day, part, answer and outcome"));
+            };
+
+            Ok(Attempt {
+                day: line.integer(day)?,
+                part: line.integer(part)?,
+                answer: answer.text().to_owned(),
+                outcome: Outcome::from_name(outcome.text()).ok_or_else(|| {
+                    line.error(
+                        outcome.start(),
+                        format!("unknown outcome '{}'", outcome.text()),
+                    )
+                })?,
+            })
+        })
+        .collect()
+}
+
+/// Submits `answer` unless the store already knows it is wrong, and records
+/// the outcome. A known correct answer is not submitted again.
+pub fn submit_answer(
+    client: &Client,
+    store: &mut AnswerStore,
+    day: u32,
+    part: u32,
+    answer: &str,
+) -> Result<Outcome, SiteError> {
+    check_day(day)?;
+    if !(1..=2).contains(&part) {
+        return Err(SiteError::InvalidPart(part));
+    }
+    if store.correct_answer(day, part) == Some(answer) {
+        return Ok(Outcome::Correct);
+    }
+    if let Some(reason) = store.known_wrong(day, part, answer) {
+        return Err(SiteError::KnownWrong {
+            answer: answer.to_owned(),
+            reason,
+        });
+    }
+
+    let page = client.post_form(
+        &format!("/{YEAR}/day/{day}/answer"),
+        &[("level", &part.to_string()), ("answer", answer)],
+    )?;
+    let outcome = Outcome::parse(&page).ok_or(SiteError::UnexpectedPage)?;
+    store.record(Attempt {
+        day,
+        part,
+        answer: answer.to_owned(),
+        outcome,
+    })?;
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use super::*;
+    use crate::site::mock::Server;
+
+    fn page(message: &str) -> String {
+        format!("<html><body><main><article><p>{message}</p></article></main></body></html>")
+    }
+
+    #[test]
+    fn outcomes() {
+        assert_eq!(
+            Some(Outcome::Correct),
+            Outcome::parse(&page(
+                "That's the right answer! You are one gold star closer."
+            ))
+        );
+        assert_eq!(
+            Some(Outcome::TooLow),
+            Outcome::parse(&page(
+                "That's not the right answer; your answer is too low. Please wait one minute."
+            ))
+        );
+        assert_eq!(
+            Some(Outcome::Incorrect),
+            Outcome::parse(&page(
+                "That's not the right answer. Please wait one minute."
+            ))
+        );
+        assert_eq!(
+            Some(Outcome::RateLimited {
+                wait: Some(Duration::from_secs(65))
+            }),
+            Outcome::parse(&page(
+                "You gave an answer too recently. You have 1m 5s left to wait."
+            ))
+        );
+        assert_eq!(
+            Some(Outcome::RateLimited { wait: None }),
+            Outcome::parse(&page(
+                "You gave an answer too recently. You have 5é left to wait."
+            ))
+        );
+        assert_eq!(
+            Some(Outcome::RateLimited { wait: None }),
+            Outcome::parse(&page(
+                "You gave an answer too recently. You have 18446744073709551615h left to wait."
+            ))
+        );
+        assert_eq!(
+            Some(Outcome::WrongLevel),
+            Outcome::parse(&page("You don't seem to be solving the right level."))
+        );
+        assert_eq!(None, Outcome::parse("<html></html>"));
+    }
+
+    #[test]
+    fn refuses_known_wrong_answers() {
+        let submissions = AtomicU32::new(0);
+        let server = Server::start(move |request| {
+            let message = match (
+                submissions.fetch_add(1, Ordering::SeqCst),
+                request.body.as_str(),
+            ) {
+                (_, "level=1&answer=100") => {
+                    "That's not the right answer; your answer is too high."
+                }
+                (0, _) => "You gave an answer too recently. You have 30s left to wait.",
+                (_, "level=1&answer=42") => "That's the right answer!",
+                _ => "That's not the right answer.",
+            };
+            (200, page(message))
+        });
+        let client = Client::new(server.url.clone(), Some("secret".to_owned()));
+        let path = env::temp_dir().join(format!("aoc-answers-{}.tsv", std::process::id()));
+        let mut store = AnswerStore::open(&path).unwrap();
+        let mut submit = |answer| submit_answer(&client, &mut store, 6, 1, answer);
+
+        assert_eq!(
+            Outcome::RateLimited {
+                wait: Some(Duration::from_secs(30))
+            },
+            submit("41").unwrap()
+        );
+        assert_eq!(Outcome::TooHigh, submit("100").unwrap());
+        assert!(matches!(
+            submit("150"),
+            Err(SiteError::KnownWrong { reason, .. }) if reason == "100 was too high"
+        ));
+        assert_eq!(Outcome::Correct, submit("42").unwrap());
+        assert_eq!(Outcome::Correct, submit("42").unwrap());
+        assert!(matches!(submit("43"), Err(SiteError::KnownWrong { .. })));
+
+        let requests = server.requests();
+        assert_eq!(3, requests.len());
+        assert_eq!(
+            ("POST", "/2023/day/6/answer"),
+            (requests[0].method.as_str(), requests[0].path.as_str())
+        );
+
+        let store = AnswerStore::open(&path).unwrap();
+        assert_eq!(
+            "6\t1\t100\ttoo-high\n6\t1\t42\tcorrect\n",
+            fs::read_to_string(&path).unwrap()
+        );
+        assert_eq!(Some("42"), store.correct_answer(6, 1));
+        assert_eq!(None, store.correct_answer(6, 2));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn store_errors() {
+        assert_eq!(
+            ParseError {
+                line: 2,
+                column: 7,
+                message: "unknown outcome 'maybe'".to_owned()
+            },
+            parse_attempts("1\t1\t5\tcorrect\n1\t2\t6\tmaybe").unwrap_err()
+        );
+    }
+}